@@ -333,6 +333,110 @@ fn insert_remove(c: &mut Criterion) {
     }
 }
 
+fn batch(c: &mut Criterion) {
+    const BATCH_SIZE: u64 = 256;
+
+    let mut group = c.benchmark_group("batch");
+
+    // Cache an immutable setup to avoid re-creating the testees for each benchmark.
+    let mut idr_loop_testee = None;
+    let mut idr_batch_testee = None;
+
+    for contention in contentions() {
+        group.bench_with_input(
+            BenchmarkId::new("idr-loop", contention),
+            &contention,
+            |b, _| {
+                let testee = idr_loop_testee.get_or_insert_with(IdrLoopTestee::new);
+                b.iter_custom(|iter_count| run(contention, iter_count, testee));
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("idr-batch", contention),
+            &contention,
+            |b, _| {
+                let testee = idr_batch_testee.get_or_insert_with(IdrBatchTestee::new);
+                b.iter_custom(|iter_count| run(contention, iter_count, testee));
+            },
+        );
+    }
+    group.finish();
+
+    fn make_idr() -> idr_ebr::Idr<Value> {
+        let idr = idr_ebr::Idr::new();
+
+        let keys = (0u64..100_000)
+            .map(|i| (idr.insert(Value(i)).unwrap(), i))
+            .filter(|(_, i)| i % 2 == 0)
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+
+        // Remove every other entry.
+        assert_eq!(idr.remove_many(&keys), keys.len()); // sanity check
+
+        idr
+    }
+
+    struct IdrLoopTestee {
+        idr: idr_ebr::Idr<Value>,
+    }
+
+    impl IdrLoopTestee {
+        fn new() -> Self {
+            Self { idr: make_idr() }
+        }
+    }
+
+    impl Testee for IdrLoopTestee {
+        type State = Vec<idr_ebr::Key>;
+
+        fn make_state(&self, _thread_no: u32) -> Self::State {
+            Vec::with_capacity(BATCH_SIZE as usize)
+        }
+
+        fn exec(&self, keys: &mut Self::State) {
+            keys.extend((0..BATCH_SIZE).map(|i| self.idr.insert(Value(i)).unwrap()));
+
+            let guard = idr_ebr::EbrGuard::new();
+            for key in &*keys {
+                black_box(self.idr.get(*key, &guard));
+            }
+            drop(guard);
+
+            for key in keys.drain(..) {
+                self.idr.remove(key);
+            }
+        }
+    }
+
+    struct IdrBatchTestee {
+        idr: idr_ebr::Idr<Value>,
+    }
+
+    impl IdrBatchTestee {
+        fn new() -> Self {
+            Self { idr: make_idr() }
+        }
+    }
+
+    impl Testee for IdrBatchTestee {
+        type State = ();
+
+        fn make_state(&self, _thread_no: u32) -> Self::State {}
+
+        fn exec(&self, (): &mut Self::State) {
+            let keys = self.idr.insert_many((0..BATCH_SIZE).map(Value));
+
+            let guard = idr_ebr::EbrGuard::new();
+            black_box(self.idr.get_many(&keys, &guard));
+            drop(guard);
+
+            self.idr.remove_many(&keys);
+        }
+    }
+}
+
 fn max_parallelism() -> u32 {
     std::env::var("BENCH_MAX_PARALLELISM").ok().map_or_else(
         || {
@@ -355,7 +459,7 @@ fn contentions() -> Vec<u32> {
         .collect()
 }
 
-criterion_group!(cases, only_read, insert_remove);
+criterion_group!(cases, only_read, insert_remove, batch);
 criterion_main!(cases);
 
 #[global_allocator]
@@ -0,0 +1,74 @@
+use std::{alloc::Layout, ptr::NonNull};
+
+use crate::loom::alloc;
+
+// === PageAllocator ===
+
+/// An allocator used to allocate and deallocate slots of IDR pages.
+///
+/// It's a small stable alternative to the unstable `std::alloc::Allocator`,
+/// see [`Idr::new_in()`] for details.
+///
+/// Only pages are allocated using this allocator. EBR containers of values
+/// are allocated by the [`sdd`] crate using the global allocator.
+///
+/// # Safety
+///
+/// Memory blocks returned by [`PageAllocator::allocate()`] must be valid for
+/// reads and writes for the requested layout (size and alignment) and must
+/// stay valid until passed to [`PageAllocator::deallocate()`].
+///
+/// [`Idr::new_in()`]: crate::Idr::new_in
+/// [`sdd`]: https://crates.io/crates/sdd
+pub unsafe trait PageAllocator {
+    /// Allocates a block of memory for the provided layout,
+    /// or returns `None` if the allocator fails.
+    ///
+    /// The layout is guaranteed to have a non-zero size.
+    ///
+    /// It's allowed to panic, the IDR remains consistent in this case.
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>>;
+
+    /// Deallocates the block of memory.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must be allocated by this allocator.
+    /// * `layout` must be the same layout that was used to allocate the block.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+// SAFETY: Delegates to the allocator behind the reference.
+unsafe impl<A: PageAllocator + ?Sized> PageAllocator for &A {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        (**self).allocate(layout)
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout);
+    }
+}
+
+// === Global ===
+
+/// The global memory allocator, which is used by default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Global;
+
+// SAFETY: `alloc::alloc()` returns a block valid for the layout.
+unsafe impl PageAllocator for Global {
+    #[inline]
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        debug_assert_ne!(layout.size(), 0);
+
+        // SAFETY: `layout` is non-zero according to the trait contract.
+        NonNull::new(unsafe { alloc::alloc(layout) })
+    }
+
+    #[inline]
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        alloc::dealloc(ptr.as_ptr(), layout);
+    }
+}
@@ -41,6 +41,76 @@ pub trait Config: Sized {
     /// **Must** be less than or equal to 32.
     const RESERVED_BITS: u32 = DefaultConfig::RESERVED_BITS;
 
+    /// Enables the strict mode, in which reserved bits are stored in slots
+    /// and checked by all methods accessing entries by a key.
+    ///
+    /// By default, reserved bits are ignored by the IDR. In the strict mode,
+    /// methods like [`Idr::get()`] and [`Idr::remove()`] ignore a key if its
+    /// reserved bits differ from ones provided to [`Idr::insert_with_reserved()`].
+    /// Also, keys returned by iterators contain stored reserved bits.
+    ///
+    /// Reserved bits are stored along with the generation in 4 bytes, so
+    /// `RESERVED_BITS + GENERATION_BITS` **must** be less than or equal to 32.
+    /// In other words, slot ids must take exactly 32 bits, which is the case
+    /// for the default values of `INITIAL_PAGE_SIZE` and `MAX_PAGES`.
+    ///
+    /// [`Idr::get()`]: crate::Idr::get
+    /// [`Idr::remove()`]: crate::Idr::remove
+    /// [`Idr::insert_with_reserved()`]: crate::Idr::insert_with_reserved
+    const STRICT_RESERVED_BITS: bool = false;
+
+    /// Enables instance tags: every IDR draws a random non-zero tag on
+    /// creation and stamps it into reserved bits of all keys it produces.
+    ///
+    /// Methods accessing entries by a key, like [`Idr::get()`] and
+    /// [`Idr::remove()`], reject keys with another tag before accessing slots.
+    /// It allows detecting keys of other IDRs (e.g. received as raw `u64`
+    /// from FFI) at runtime, see [`TypedKey`] for compile-time checks.
+    ///
+    /// The tag takes all `RESERVED_BITS`, so a foreign key is accepted with
+    /// a probability of `1 / (2**RESERVED_BITS - 1)`. Tags are stored in slots
+    /// like in the strict mode, thus:
+    /// * `RESERVED_BITS` **must** be positive.
+    /// * `RESERVED_BITS + GENERATION_BITS` **must** be less than or equal to 32.
+    /// * It cannot be combined with `STRICT_RESERVED_BITS`.
+    ///
+    /// [`Idr::get()`]: crate::Idr::get
+    /// [`Idr::remove()`]: crate::Idr::remove
+    /// [`TypedKey`]: crate::TypedKey
+    const INSTANCE_TAG: bool = false;
+
+    /// Retires a slot permanently instead of reusing it once its generation
+    /// counter would wrap around.
+    ///
+    /// By default, the generation counter wraps, so after `2^GENERATION_BITS`
+    /// reuses of the same slot a stale key resolves into a new entry again.
+    /// With a large `RESERVED_BITS`, there are few generation bits, so it can
+    /// happen quickly. If this policy is enabled, the slot isn't freed after
+    /// removing the entry of the last generation, so stale keys never resolve.
+    ///
+    /// The price is that the effective capacity of the IDR shrinks with every
+    /// retired slot, see [`Idr::retired()`] and [`Stats::retired`]. Also,
+    /// pages with retired slots are never deallocated by [`Idr::shrink_to_fit()`].
+    ///
+    /// [`Idr::retired()`]: crate::Idr::retired
+    /// [`Idr::shrink_to_fit()`]: crate::Idr::shrink_to_fit
+    /// [`Stats::retired`]: crate::Stats::retired
+    const RETIRE_SLOTS_ON_WRAP: bool = false;
+
+    /// The order in which free slots are reused, see [`ReusePolicy`].
+    ///
+    /// By default, the most recently freed slot is reused first.
+    const REUSE_POLICY: ReusePolicy = ReusePolicy::Lifo;
+
+    /// The capacity of the change feed, see [`Idr::changes()`].
+    ///
+    /// If it's zero (the default), the change feed is disabled. Otherwise,
+    /// every insertion, replacement and removal allocates a record, and the
+    /// feed retains the last `CHANGE_FEED_CAPACITY` events.
+    ///
+    /// [`Idr::changes()`]: crate::Idr::changes
+    const CHANGE_FEED_CAPACITY: usize = 0;
+
     /// Returns a debug representation of the configuration, which includes all
     /// internally calculated values and limits.
     #[must_use]
@@ -62,12 +132,61 @@ impl Config for DefaultConfig {
     const RESERVED_BITS: u32 = 0;
 }
 
+/// A policy of reusing free slots, see [`Config::REUSE_POLICY`].
+///
+/// Slots are reused within a page; pages are still filled in order,
+/// so the policy doesn't affect which page a new entry is inserted into.
+///
+/// Regardless of the policy, a reused slot gets the next generation, so
+/// stale keys don't resolve into new entries until the generation wraps.
+/// However, the longer a slot stays free, the fewer generations are spent,
+/// and the less likely clients confuse a new key with a removed one.
+///
+/// Policies other than [`ReusePolicy::Lifo`] move freed slots to the free list
+/// in batches. Insertion stays lock-free, so it doesn't wait for another thread
+/// moving a batch and proceeds to the next page instead. Thus, if all pages
+/// are allocated, insertion can fail while slots are being moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReusePolicy {
+    /// The most recently freed slot is reused first. It's the cheapest one
+    /// and keeps recently used memory in the cache.
+    Lifo,
+    /// The least recently freed slot is reused first.
+    ///
+    /// Freed slots are collected in a separate list, which is moved to the
+    /// free list only when it runs out, so the order is exact only without
+    /// concurrent insertions.
+    Fifo,
+    /// A freed slot isn't reused until an EBR grace period has passed since
+    /// its removal, i.e. until all [`EbrGuard`]s existing at the moment of
+    /// the removal are dropped.
+    ///
+    /// Quarantined slots are counted as free by [`Idr::stats()`], but they
+    /// cannot be taken, so new pages are allocated instead if there are
+    /// no other free slots. Once all pages are allocated, insertion fails
+    /// until the grace period passes. Guards should be short-lived for this
+    /// policy.
+    ///
+    /// [`EbrGuard`]: crate::EbrGuard
+    /// [`Idr::stats()`]: crate::Idr::stats
+    Quarantine,
+}
+
 pub(crate) trait ConfigPrivate: Config {
     const USED_BITS: u32 = 64 - Self::RESERVED_BITS;
     const SLOT_BITS: u32 = Self::MAX_PAGES + Self::INITIAL_PAGE_SIZE.trailing_zeros();
     const SLOT_MASK: u32 = ((1u64 << Self::SLOT_BITS) - 1) as u32;
     const GENERATION_BITS: u32 = Self::USED_BITS - Self::SLOT_BITS;
     const GENERATION_MASK: u32 = ((1u64 << Self::GENERATION_BITS) - 1) as u32;
+    // In the strict mode and with instance tags, reserved bits are stored in slots
+    // along with the generation.
+    const STORES_RESERVED_BITS: bool = Self::STRICT_RESERVED_BITS || Self::INSTANCE_TAG;
+    const STORED_GENERATION_MASK: u32 = if Self::STORES_RESERVED_BITS {
+        u32::MAX
+    } else {
+        Self::GENERATION_MASK
+    };
 
     // For debugging and tests, both values are `<= u32::MAX + 1`.
     const MAX_SLOTS: u64 = ((1u64 << Self::MAX_PAGES) - 1) * Self::INITIAL_PAGE_SIZE as u64;
@@ -81,6 +200,9 @@ pub(crate) trait ConfigPrivate: Config {
         assert!(Self::RESERVED_BITS <= 32);
         assert!(Self::SLOT_BITS <= 32);
         assert!(Self::GENERATION_BITS <= 32);
+        assert!(!Self::STORES_RESERVED_BITS || Self::RESERVED_BITS + Self::GENERATION_BITS <= 32);
+        assert!(!Self::INSTANCE_TAG || Self::RESERVED_BITS > 0);
+        assert!(!Self::INSTANCE_TAG || !Self::STRICT_RESERVED_BITS);
         true
     };
 }
@@ -95,6 +217,11 @@ impl<C: Config> Debug for DebugConfig<C> {
             .field("INITIAL_PAGE_SIZE", &C::INITIAL_PAGE_SIZE)
             .field("MAX_PAGES", &C::MAX_PAGES)
             .field("RESERVED_BITS", &C::RESERVED_BITS)
+            .field("STRICT_RESERVED_BITS", &C::STRICT_RESERVED_BITS)
+            .field("INSTANCE_TAG", &C::INSTANCE_TAG)
+            .field("RETIRE_SLOTS_ON_WRAP", &C::RETIRE_SLOTS_ON_WRAP)
+            .field("REUSE_POLICY", &C::REUSE_POLICY)
+            .field("CHANGE_FEED_CAPACITY", &C::CHANGE_FEED_CAPACITY)
             .field("USED_BITS", &C::USED_BITS)
             .field("SLOT_BITS", &C::SLOT_BITS)
             .field("GENERATION_BITS", &C::GENERATION_BITS)
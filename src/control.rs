@@ -1,4 +1,4 @@
-use std::cell::Cell;
+use std::{cell::Cell, sync::PoisonError};
 
 use fastrand::Rng;
 
@@ -10,47 +10,53 @@ use crate::loom::{
     thread_local,
 };
 
-pub(crate) struct PageControl {
+pub(crate) struct PageControl<A> {
     // Used to synchronize page allocations.
     lock: Mutex<()>,
 
     // Used to distribute `Idr::insert()` across existing pages.
     // It improves performance by reducing contention.
     allocated: AtomicU32,
+
+    // Used to allocate and deallocate pages.
+    alloc: A,
 }
 
-impl Default for PageControl {
-    fn default() -> Self {
+impl<A> PageControl<A> {
+    pub(crate) fn new(alloc: A) -> Self {
         Self {
             allocated: AtomicU32::new(0),
             lock: Mutex::new(()),
+            alloc,
         }
     }
-}
 
-impl PageControl {
-    pub(crate) fn get_or_lock<R>(
+    pub(crate) fn get_or_lock<R, E>(
         &self,
         get: impl Fn() -> *const R,
-        alloc: impl FnOnce(),
-    ) -> *const R {
+        alloc: impl FnOnce() -> Result<(), E>,
+    ) -> Result<*const R, E> {
         let ptr = get();
 
         // The fast path, the page is already allocated.
         if !ptr.is_null() {
-            return ptr;
+            return Ok(ptr);
         }
 
-        let _guard = self.lock.lock().expect("lock poisoned");
+        // The lock protects no data, and the page state is changed only once
+        // `alloc()` succeeds. Thus, if `alloc()` panics, nothing is changed,
+        // and it's safe to ignore poisoning and try to allocate again.
+        let _guard = self.lock.lock().unwrap_or_else(PoisonError::into_inner);
 
         // Re-check if the page is allocated while acquiring the lock.
         let ptr = get();
         if !ptr.is_null() {
-            return ptr;
+            return Ok(ptr);
         }
 
         // Actually allocate the page.
-        alloc();
+        // If it fails, nothing is changed, so the next call will try again.
+        alloc()?;
         let ptr = get();
         debug_assert!(!ptr.is_null());
 
@@ -58,7 +64,7 @@ impl PageControl {
         // it's only for performance optimization and doesn't affect correctness.
         self.allocated.fetch_add(1, Ordering::Relaxed);
 
-        ptr
+        Ok(ptr)
     }
 
     pub(crate) fn choose<'a, P, R>(
@@ -95,12 +101,24 @@ impl PageControl {
         None
     }
 
+    pub(crate) fn deallocated(&self) {
+        // See `get_or_lock()` for details about the ordering.
+        let prev = self.allocated.fetch_sub(1, Ordering::Relaxed);
+        debug_assert!(prev > 0);
+    }
+
     pub(crate) fn allocated(&self) -> u32 {
         self.allocated.load(Ordering::Relaxed)
     }
+
+    pub(crate) fn allocator(&self) -> &A {
+        &self.alloc
+    }
 }
 
 thread_local! {
+    // Keep compatible with `loom::thread_local!`.
+    #[allow(clippy::missing_const_for_thread_local)]
     static RNG: Cell<Rng> = Cell::new(Rng::with_seed(0xef6_f79e_d30b_a75a));
 }
 
@@ -112,3 +130,39 @@ fn gen_u32(upper: u32) -> u32 {
         ret
     })
 }
+
+#[test]
+fn test_poisoned_lock() {
+    use std::{panic, ptr};
+
+    let control = PageControl::new(());
+    let page = 42;
+
+    // Panic while allocating.
+    let result = panic::catch_unwind(|| {
+        control.get_or_lock(ptr::null::<i32>, || -> Result<(), ()> { panic!("oops") })
+    });
+    assert!(result.is_err());
+    assert_eq!(control.allocated(), 0);
+
+    // Fail while allocating.
+    let result = control.get_or_lock(ptr::null::<i32>, || Err(()));
+    assert_eq!(result, Err(()));
+    assert_eq!(control.allocated(), 0);
+
+    // Retry allocating.
+    let allocated = Cell::new(false);
+    let get = || {
+        if allocated.get() {
+            ptr::from_ref(&page)
+        } else {
+            ptr::null()
+        }
+    };
+    let result = control.get_or_lock(get, || {
+        allocated.set(true);
+        Ok::<_, ()>(())
+    });
+    assert_eq!(result, Ok(ptr::from_ref(&page)));
+    assert_eq!(control.allocated(), 1);
+}
@@ -0,0 +1,172 @@
+use std::{error::Error, fmt};
+
+// === InsertError ===
+
+/// An error returned by [`Idr::try_insert()`] and [`Idr::try_vacant_entry()`].
+///
+/// Contains the value that failed to be inserted, if any.
+///
+/// [`Idr::try_insert()`]: crate::Idr::try_insert
+/// [`Idr::try_vacant_entry()`]: crate::Idr::try_vacant_entry
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum InsertError<T = ()> {
+    /// There are no free slots within [`Config::MAX_PAGES`] pages,
+    /// and no items can be added until some are removed.
+    ///
+    /// [`Config::MAX_PAGES`]: crate::Config::MAX_PAGES
+    Full(T),
+    /// A new page should be allocated, but the allocator fails.
+    AllocFailed(T),
+}
+
+impl<T> InsertError<T> {
+    /// Returns the value that failed to be inserted.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(value) | Self::AllocFailed(value) => value,
+        }
+    }
+
+    pub(crate) fn with_value<U>(self, value: U) -> InsertError<U> {
+        match self {
+            Self::Full(_) => InsertError::Full(value),
+            Self::AllocFailed(_) => InsertError::AllocFailed(value),
+        }
+    }
+}
+
+impl<T> fmt::Debug for InsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("Full(..)"),
+            Self::AllocFailed(_) => f.write_str("AllocFailed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for InsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("no free slots in the IDR"),
+            Self::AllocFailed(_) => f.write_str("failed to allocate a new page"),
+        }
+    }
+}
+
+impl<T> Error for InsertError<T> {}
+
+// === InsertAtError ===
+
+/// An error returned by [`Idr::insert_at()`].
+///
+/// Contains the value that failed to be inserted.
+///
+/// [`Idr::insert_at()`]: crate::Idr::insert_at
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum InsertAtError<T = ()> {
+    /// The key doesn't refer to any slot, see [`Key::try_from_raw()`].
+    ///
+    /// [`Key::try_from_raw()`]: crate::Key::try_from_raw
+    InvalidKey(T),
+    /// The slot is occupied or reserved by a [`VacantEntry`].
+    ///
+    /// [`VacantEntry`]: crate::VacantEntry
+    Occupied(T),
+    /// A new page should be allocated, but the allocator fails.
+    AllocFailed(T),
+}
+
+impl<T> InsertAtError<T> {
+    /// Returns the value that failed to be inserted.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::InvalidKey(value) | Self::Occupied(value) | Self::AllocFailed(value) => value,
+        }
+    }
+}
+
+impl<T> fmt::Debug for InsertAtError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey(_) => f.write_str("InvalidKey(..)"),
+            Self::Occupied(_) => f.write_str("Occupied(..)"),
+            Self::AllocFailed(_) => f.write_str("AllocFailed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for InsertAtError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidKey(_) => f.write_str("the key doesn't refer to any slot"),
+            Self::Occupied(_) => f.write_str("the slot is occupied"),
+            Self::AllocFailed(_) => f.write_str("failed to allocate a new page"),
+        }
+    }
+}
+
+impl<T> Error for InsertAtError<T> {}
+
+// === InvalidKey ===
+
+/// An error returned by [`Key::try_from_raw()`].
+///
+/// [`Key::try_from_raw()`]: crate::Key::try_from_raw
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[non_exhaustive]
+pub enum InvalidKey {
+    /// The key is zero.
+    Zero,
+    /// The key doesn't refer to any page.
+    NoPage,
+}
+
+impl fmt::Display for InvalidKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Zero => f.write_str("the key is zero"),
+            Self::NoPage => f.write_str("the key doesn't refer to any page"),
+        }
+    }
+}
+
+impl Error for InvalidKey {}
+
+// === Lagged ===
+
+/// An error returned by [`Changes`] if the reader lags behind the change feed,
+/// and some events have been overwritten by newer ones.
+///
+/// The reader continues from the oldest retained event, but a follower
+/// should be synchronized again, e.g. by copying all entries.
+///
+/// [`Changes`]: crate::Changes
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Lagged(pub(crate) u64);
+
+impl Lagged {
+    /// Returns the number of skipped events.
+    #[must_use]
+    #[inline]
+    pub fn skipped(self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for Lagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lagged behind the change feed by {} events", self.0)
+    }
+}
+
+impl Error for Lagged {}
+
+// === AllocError ===
+
+/// The allocator failed to allocate a new page.
+#[derive(Debug)]
+pub(crate) struct AllocError;
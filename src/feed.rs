@@ -0,0 +1,185 @@
+use std::fmt;
+
+use sdd::{AtomicShared, Guard, Shared, Tag};
+
+use crate::{
+    error::Lagged,
+    key::Key,
+    loom::sync::atomic::{AtomicU64, Ordering},
+};
+
+// === ChangeEvent ===
+
+/// An event of the change feed, see [`Idr::changes()`].
+///
+/// Events contain only keys, values should be read from the IDR by them.
+///
+/// [`Idr::changes()`]: crate::Idr::changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ChangeEvent {
+    /// A value has been inserted at the key.
+    Inserted(Key),
+    /// A value at the key has been replaced.
+    Replaced(Key),
+    /// A value at the key has been removed.
+    Removed(Key),
+}
+
+impl ChangeEvent {
+    /// Returns the key of the event.
+    #[must_use]
+    #[inline]
+    pub fn key(self) -> Key {
+        match self {
+            Self::Inserted(key) | Self::Replaced(key) | Self::Removed(key) => key,
+        }
+    }
+}
+
+// === ChangeFeed ===
+
+/// A bounded lock-free ring of recent events.
+///
+/// Every event gets a sequence number, and the newest event overwrites the
+/// oldest one in the same cell. Events are recorded after the operation takes
+/// effect, so a reader of an event observes the effect of the operation.
+pub(crate) struct ChangeFeed {
+    // Empty if the feed is disabled.
+    cells: Box<[AtomicShared<Record>]>,
+    // The sequence number of the next event.
+    tail: AtomicU64,
+}
+
+struct Record {
+    seq: u64,
+    event: ChangeEvent,
+}
+
+impl ChangeFeed {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            cells: (0..capacity).map(|_| AtomicShared::null()).collect(),
+            tail: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.cells.is_empty()
+    }
+
+    /// Records the event, if the feed is enabled.
+    ///
+    /// Removals must be recorded before the slot is freed, so that the next
+    /// insertion into the same slot is always recorded after the removal.
+    #[inline]
+    pub(crate) fn record(&self, event: ChangeEvent) {
+        if self.is_enabled() {
+            self.push(event);
+        }
+    }
+
+    fn push(&self, event: ChangeEvent) {
+        // `AcqRel` here makes every previous operation, which has been recorded
+        // earlier, visible to readers of this event.
+        let seq = self.tail.fetch_add(1, Ordering::AcqRel);
+
+        let cell = &self.cells[self.index(seq)];
+        let mut record = Shared::new(Record { seq, event });
+
+        let guard = Guard::new();
+        let mut current = cell.load(Ordering::Acquire, &guard);
+
+        loop {
+            // The cell has been overwritten by a newer event already,
+            // so this event is lost for lagging readers anyway.
+            if current.as_ref().is_some_and(|current| current.seq > seq) {
+                return;
+            }
+
+            match cell.compare_exchange(
+                current,
+                (Some(record), Tag::None),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                &guard,
+            ) {
+                Ok(_) => return,
+                Err((returned, actual)) => {
+                    // SAFETY: The provided record is always returned back on failure.
+                    record = unsafe { returned.unwrap_unchecked() };
+                    current = actual;
+                }
+            }
+        }
+    }
+
+    fn index(&self, seq: u64) -> usize {
+        // It never truncates, because the result is less than the length.
+        (seq % self.cells.len() as u64) as usize
+    }
+}
+
+// === Changes ===
+
+/// A reader of the change feed, see [`Idr::changes()`].
+///
+/// The iterator isn't fused: it returns `None` if there are no new events
+/// yet, but can return new events later.
+///
+/// [`Idr::changes()`]: crate::Idr::changes
+#[must_use]
+pub struct Changes<'s> {
+    feed: &'s ChangeFeed,
+    next: u64,
+}
+
+impl<'s> Changes<'s> {
+    pub(crate) fn new(feed: &'s ChangeFeed) -> Self {
+        Self {
+            feed,
+            next: feed.tail.load(Ordering::Acquire),
+        }
+    }
+}
+
+impl Iterator for Changes<'_> {
+    type Item = Result<ChangeEvent, Lagged>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.feed.is_enabled() {
+            return None;
+        }
+
+        let guard = Guard::new();
+        let cell = &self.feed.cells[self.feed.index(self.next)];
+        let record = cell.load(Ordering::Acquire, &guard);
+
+        match record.as_ref() {
+            Some(record) if record.seq == self.next => {
+                self.next += 1;
+                Some(Ok(record.event))
+            }
+            // The event has been overwritten, skip to the oldest retained one.
+            Some(record) if record.seq > self.next => {
+                let tail = self.feed.tail.load(Ordering::Acquire);
+                let oldest = tail.saturating_sub(self.feed.cells.len() as u64);
+                let next = oldest.max(self.next + 1);
+
+                let skipped = next - self.next;
+                self.next = next;
+                Some(Err(Lagged(skipped)))
+            }
+            // The event hasn't been recorded yet.
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for Changes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Changes")
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,11 +1,13 @@
-use std::{fmt, mem, ops::Deref};
+use std::{fmt, iter::FusedIterator, marker::PhantomData, mem, ops::Deref, slice};
 
 use crate::{
+    allocator::PageAllocator,
     config::Config,
-    key::Key,
+    feed::{ChangeEvent, ChangeFeed},
+    key::{IdrKey, Key},
     page::{self, Page},
     slot::Slot,
-    EbrGuard,
+    EbrGuard, Idr,
 };
 
 // === VacantEntry ===
@@ -18,15 +20,32 @@ use crate::{
 ///
 /// [`Idr::vacant_entry()`]: crate::Idr::vacant_entry
 #[must_use]
-pub struct VacantEntry<'s, T: 'static, C: Config> {
+pub struct VacantEntry<'s, T: 'static, C: Config, K: IdrKey = Key> {
     page: &'s Page<T, C>,
     slot: &'s Slot<T, C>,
     key: Key,
+    feed: &'s ChangeFeed,
+    _key: PhantomData<fn() -> K>,
 }
 
-impl<'s, T: 'static, C: Config> VacantEntry<'s, T, C> {
-    pub(crate) fn new(page: &'s Page<T, C>, slot: &'s Slot<T, C>, key: Key) -> Self {
-        Self { page, slot, key }
+impl<'s, T: 'static, C: Config, K: IdrKey> VacantEntry<'s, T, C, K> {
+    pub(crate) fn new(
+        page: &'s Page<T, C>,
+        slot: &'s Slot<T, C>,
+        key: Key,
+        feed: &'s ChangeFeed,
+    ) -> Self {
+        Self {
+            page,
+            slot,
+            key,
+            feed,
+            _key: PhantomData,
+        }
+    }
+
+    pub(crate) fn set_reserved(&mut self, bits: u32) {
+        self.key = self.key.with_reserved::<C>(bits);
     }
 
     /// Returns the key at which this entry will be inserted.
@@ -34,8 +53,8 @@ impl<'s, T: 'static, C: Config> VacantEntry<'s, T, C> {
     /// An entry stored in this entry will be associated with this key.
     #[must_use]
     #[inline]
-    pub fn key(&self) -> Key {
-        self.key
+    pub fn key(&self) -> K {
+        K::from_key(self.key)
     }
 
     /// Inserts a value in the IDR.
@@ -46,12 +65,13 @@ impl<'s, T: 'static, C: Config> VacantEntry<'s, T, C> {
     /// [`VacantEntry::key()`] prior to calling this method.
     #[inline]
     pub fn insert(self, value: T) {
-        self.slot.init(value);
+        self.slot.init(self.key, value);
+        self.feed.record(ChangeEvent::Inserted(self.key));
         mem::forget(self);
     }
 }
 
-impl<T: 'static, C: Config> Drop for VacantEntry<'_, T, C> {
+impl<T: 'static, C: Config, K: IdrKey> Drop for VacantEntry<'_, T, C, K> {
     #[inline]
     fn drop(&mut self) {
         // SAFETY: The slot belongs to this page by construction.
@@ -59,7 +79,7 @@ impl<T: 'static, C: Config> Drop for VacantEntry<'_, T, C> {
     }
 }
 
-impl<T, C: Config> fmt::Debug for VacantEntry<'_, T, C> {
+impl<T, C: Config, K: IdrKey> fmt::Debug for VacantEntry<'_, T, C, K> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("VacantEntry")
             .field("key", &self.key)
@@ -82,9 +102,14 @@ impl<'g, T> BorrowedEntry<'g, T> {
         (!ptr.is_null()).then_some(Self(ptr))
     }
 
+    pub(crate) fn as_ptr(self) -> sdd::Ptr<'g, T> {
+        self.0
+    }
+
     /// Creates an owned handle to the entry.
     ///
-    /// It returns `None` if the entry has been removed from the IDR.
+    /// It returns `None` if the entry has been removed from the IDR
+    /// (or replaced) and no other owned handles exist.
     ///
     /// This method is lock-free, but it modifies the memory by incrementing the
     /// reference counter.
@@ -92,7 +117,7 @@ impl<'g, T> BorrowedEntry<'g, T> {
     /// See [`OwnedEntry`] for more details.
     #[inline]
     pub fn to_owned(self) -> Option<OwnedEntry<T>> {
-        self.0.get_shared().map(OwnedEntry)
+        self.0.get_shared().map(OwnedEntry::new)
     }
 }
 
@@ -140,6 +165,12 @@ impl<T: PartialEq<T>> PartialEq<T> for BorrowedEntry<'_, T> {
 #[must_use]
 pub struct OwnedEntry<T>(sdd::Shared<T>);
 
+impl<T> OwnedEntry<T> {
+    pub(crate) fn new(shared: sdd::Shared<T>) -> Self {
+        Self(shared)
+    }
+}
+
 impl<T> Clone for OwnedEntry<T> {
     #[inline]
     fn clone(&self) -> Self {
@@ -171,57 +202,337 @@ impl<T: PartialEq<T>> PartialEq<T> for OwnedEntry<T> {
 
 // === Iter ===
 
-/// A fused iterator over all occupied entries in the IDR.
+/// A fused iterator over all occupied entries in the IDR, ordered by keys.
+///
+/// It's double-ended, so entries can be also iterated in reverse order.
 ///
-/// See [`Idr::iter()`] for more details.
+/// See [`Idr::iter()`] and [`Idr::iter_from()`] for more details.
 ///
 /// [`Idr::iter()`]: crate::Idr::iter
+/// [`Idr::iter_from()`]: crate::Idr::iter_from
 #[must_use]
-pub struct Iter<'g, 's, T, C> {
+pub struct Iter<'g, 's, T, C, K = Key> {
     pages: &'s [Page<T, C>],
-    slots: Option<page::Iter<'g, 's, T, C>>,
+    front: Option<page::Iter<'g, 's, T, C>>,
+    back: Option<page::Iter<'g, 's, T, C>>,
     guard: &'g EbrGuard,
+    _key: PhantomData<fn() -> K>,
 }
 
-impl<'g, 's, T: 'static, C: Config> Iter<'g, 's, T, C> {
+impl<'g, 's, T: 'static, C: Config, K: IdrKey> Iter<'g, 's, T, C, K> {
     pub(crate) fn new(pages: &'s [Page<T, C>], guard: &'g EbrGuard) -> Self {
-        let (first, rest) = pages.split_first().expect("invalid MAX_PAGES");
+        Self {
+            pages,
+            front: None,
+            back: None,
+            guard,
+            _key: PhantomData,
+        }
+    }
+
+    pub(crate) fn from_key(pages: &'s [Page<T, C>], key: Key, guard: &'g EbrGuard) -> Self {
+        let page_no = key.page_no::<C>().to_usize();
+
+        let Some((page, rest)) = pages.get(page_no..).and_then(<[_]>::split_first) else {
+            return Self::new(&[], guard);
+        };
 
         Self {
             pages: rest,
-            slots: first.iter(guard),
+            front: page.iter_from(key, guard),
+            back: None,
             guard,
+            _key: PhantomData,
         }
     }
 }
 
-impl<'g, T: 'static, C: Config> Iterator for Iter<'g, '_, T, C> {
-    type Item = (Key, BorrowedEntry<'g, T>);
+impl<'g, T: 'static, C: Config, K: IdrKey> Iterator for Iter<'g, '_, T, C, K> {
+    type Item = (K, BorrowedEntry<'g, T>);
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            let slots = self.slots.as_mut()?;
+            if let Some((key, entry)) = self.front.as_mut().and_then(Iterator::next) {
+                return Some((K::from_key(key), entry));
+            }
 
-            if let Some(pair) = slots.next() {
-                return Some(pair);
+            // Unallocated pages are skipped, because `page.iter()` returns `None`.
+            let Some((page, rest)) = self.pages.split_first() else {
+                let (key, entry) = self.back.as_mut()?.next()?;
+                return Some((K::from_key(key), entry));
+            };
+
+            self.pages = rest;
+            self.front = page.iter(self.guard);
+        }
+    }
+}
+
+impl<T: 'static, C: Config, K: IdrKey> DoubleEndedIterator for Iter<'_, '_, T, C, K> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, entry)) = self.back.as_mut().and_then(DoubleEndedIterator::next_back)
+            {
+                return Some((K::from_key(key), entry));
             }
 
-            let (slots, rest) = self
-                .pages
-                .split_first()
-                .map(|(next, rest)| (next.iter(self.guard), rest))
-                .unwrap_or_default();
+            // Unallocated pages are skipped, because `page.iter()` returns `None`.
+            let Some((page, rest)) = self.pages.split_last() else {
+                let (key, entry) = self.front.as_mut()?.next_back()?;
+                return Some((K::from_key(key), entry));
+            };
 
             self.pages = rest;
-            self.slots = slots;
+            self.back = page.iter(self.guard);
         }
     }
 }
 
-impl<T: 'static, C: Config> std::iter::FusedIterator for Iter<'_, '_, T, C> {}
+impl<T: 'static, C: Config, K: IdrKey> FusedIterator for Iter<'_, '_, T, C, K> {}
 
-impl<T, C> fmt::Debug for Iter<'_, '_, T, C> {
+impl<T, C, K> fmt::Debug for Iter<'_, '_, T, C, K> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Iter").finish_non_exhaustive()
     }
 }
+
+// === IterMut ===
+
+/// A fused iterator over mutable references to occupied entries in the IDR.
+///
+/// See [`Idr::iter_mut()`] for more details.
+///
+/// [`Idr::iter_mut()`]: crate::Idr::iter_mut
+#[must_use]
+pub struct IterMut<'s, T, C, K = Key> {
+    pages: slice::IterMut<'s, Page<T, C>>,
+    slots: Option<page::IterMut<'s, T, C>>,
+    _key: PhantomData<fn() -> K>,
+}
+
+impl<'s, T: 'static, C: Config, K: IdrKey> IterMut<'s, T, C, K> {
+    pub(crate) fn new(pages: &'s mut [Page<T, C>]) -> Self {
+        Self {
+            pages: pages.iter_mut(),
+            slots: None,
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<'s, T: 'static, C: Config, K: IdrKey> Iterator for IterMut<'s, T, C, K> {
+    type Item = (K, &'s mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, value)) = self.slots.as_mut().and_then(Iterator::next) {
+                return Some((K::from_key(key), value));
+            }
+
+            // Pages are allocated in order, so stop on the first unallocated one.
+            self.slots = Some(self.pages.next()?.iter_mut()?);
+        }
+    }
+}
+
+impl<T: 'static, C: Config, K: IdrKey> FusedIterator for IterMut<'_, T, C, K> {}
+
+impl<T, C, K> fmt::Debug for IterMut<'_, T, C, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IterMut").finish_non_exhaustive()
+    }
+}
+
+// === Drain ===
+
+/// A draining iterator over entries in the IDR.
+///
+/// Entries are yielded as [`OwnedEntry`], because values cannot be moved out
+/// of EBR-managed allocations. If the iterator is dropped, remaining entries
+/// are removed anyway.
+///
+/// See [`Idr::drain()`] for more details.
+///
+/// [`Idr::drain()`]: crate::Idr::drain
+#[must_use]
+pub struct Drain<'s, T: 'static, C: Config, K: IdrKey = Key> {
+    pages: &'s [Page<T, C>],
+    feed: &'s ChangeFeed,
+    cursor: Cursor<K>,
+}
+
+impl<'s, T: 'static, C: Config, K: IdrKey> Drain<'s, T, C, K> {
+    pub(crate) fn new(pages: &'s [Page<T, C>], feed: &'s ChangeFeed) -> Self {
+        Self {
+            pages,
+            feed,
+            cursor: Cursor::default(),
+        }
+    }
+}
+
+impl<T: 'static, C: Config, K: IdrKey> Iterator for Drain<'_, T, C, K> {
+    type Item = (K, OwnedEntry<T>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.take_next(self.pages, self.feed)
+    }
+}
+
+impl<T: 'static, C: Config, K: IdrKey> FusedIterator for Drain<'_, T, C, K> {}
+
+impl<T: 'static, C: Config, K: IdrKey> Drop for Drain<'_, T, C, K> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+impl<T, C: Config, K: IdrKey> fmt::Debug for Drain<'_, T, C, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Drain").finish_non_exhaustive()
+    }
+}
+
+// === IntoIter ===
+
+/// An owning iterator over entries in the IDR.
+///
+/// Entries are yielded as [`OwnedEntry`], because values cannot be moved out
+/// of EBR-managed allocations.
+///
+/// See [`Idr::into_iter()`] for more details.
+///
+/// [`Idr::into_iter()`]: crate::Idr::into_iter
+#[must_use]
+pub struct IntoIter<T, C, A: PageAllocator, K: IdrKey = Key> {
+    idr: Idr<T, C, A, K>,
+    cursor: Cursor<K>,
+}
+
+impl<T: 'static, C: Config, A: PageAllocator, K: IdrKey> IntoIter<T, C, A, K> {
+    pub(crate) fn new(idr: Idr<T, C, A, K>) -> Self {
+        Self {
+            idr,
+            cursor: Cursor::default(),
+        }
+    }
+}
+
+impl<T: 'static, C: Config, A: PageAllocator, K: IdrKey> Iterator for IntoIter<T, C, A, K> {
+    type Item = (K, OwnedEntry<T>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.take_next(&self.idr.pages, &self.idr.feed)
+    }
+}
+
+impl<T: 'static, C: Config, A: PageAllocator, K: IdrKey> FusedIterator for IntoIter<T, C, A, K> {}
+
+impl<T, C: Config, A: PageAllocator, K: IdrKey> fmt::Debug for IntoIter<T, C, A, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IntoIter").finish_non_exhaustive()
+    }
+}
+
+// === IterOwned ===
+
+/// A fused iterator over all occupied entries in the IDR, ordered by keys,
+/// which doesn't hold an [`EbrGuard`].
+///
+/// See [`Idr::iter_owned()`] for more details.
+///
+/// [`Idr::iter_owned()`]: crate::Idr::iter_owned
+#[must_use]
+pub struct IterOwned<'s, T, C, K = Key> {
+    pages: &'s [Page<T, C>],
+    cursor: Cursor<K>,
+}
+
+impl<'s, T: 'static, C: Config, K: IdrKey> IterOwned<'s, T, C, K> {
+    pub(crate) fn new(pages: &'s [Page<T, C>]) -> Self {
+        Self {
+            pages,
+            cursor: Cursor::default(),
+        }
+    }
+}
+
+impl<T: 'static, C: Config, K: IdrKey> Iterator for IterOwned<'_, T, C, K> {
+    type Item = (K, OwnedEntry<T>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.cursor.get_next(self.pages)
+    }
+}
+
+impl<T: 'static, C: Config, K: IdrKey> FusedIterator for IterOwned<'_, T, C, K> {}
+
+impl<T, C, K> fmt::Debug for IterOwned<'_, T, C, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IterOwned").finish_non_exhaustive()
+    }
+}
+
+// === Cursor ===
+
+/// A position of iterators, which don't hold an [`EbrGuard`].
+struct Cursor<K> {
+    page_no: usize,
+    slot_index: u32,
+    _key: PhantomData<fn() -> K>,
+}
+
+impl<K> Default for Cursor<K> {
+    fn default() -> Self {
+        Self {
+            page_no: 0,
+            slot_index: 0,
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<K: IdrKey> Cursor<K> {
+    fn get_next<T: 'static, C: Config>(
+        &mut self,
+        pages: &[Page<T, C>],
+    ) -> Option<(K, OwnedEntry<T>)> {
+        let guard = EbrGuard::new();
+
+        while let Some(page) = pages.get(self.page_no) {
+            while let Some((key, entry)) = page.get_next(&mut self.slot_index, &guard) {
+                // Skip the entry if it has been removed concurrently.
+                if let Some(entry) = entry.to_owned() {
+                    return Some((K::from_key(key), entry));
+                }
+            }
+
+            self.page_no += 1;
+            self.slot_index = 0;
+        }
+
+        None
+    }
+
+    fn take_next<T: 'static, C: Config>(
+        &mut self,
+        pages: &[Page<T, C>],
+        feed: &ChangeFeed,
+    ) -> Option<(K, OwnedEntry<T>)> {
+        let guard = EbrGuard::new();
+
+        while let Some(page) = pages.get(self.page_no) {
+            if let Some((key, data)) = page.take_next(&mut self.slot_index, &guard, feed) {
+                return Some((K::from_key(key), OwnedEntry::new(data)));
+            }
+
+            self.page_no += 1;
+            self.slot_index = 0;
+        }
+
+        None
+    }
+}
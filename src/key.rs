@@ -1,6 +1,16 @@
-use std::{marker::PhantomData, num::NonZeroU64};
-
-use crate::config::{Config, ConfigPrivate};
+use std::{
+    cmp::Ordering,
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hash, Hasher},
+    marker::PhantomData,
+    num::NonZeroU64,
+};
+
+use crate::{
+    config::{Config, ConfigPrivate},
+    error::InvalidKey,
+};
 
 // === Key ===
 
@@ -17,6 +27,149 @@ use crate::config::{Config, ConfigPrivate};
 pub struct Key(NonZeroU64);
 
 impl Key {
+    /// Converts a raw value into a key, checking that it's structurally valid
+    /// for the provided configuration.
+    ///
+    /// It's useful to validate keys received from untrusted sources before
+    /// using them. However, a valid key doesn't mean that the IDR contains an
+    /// entry for it, only that it refers to some slot of the IDR.
+    ///
+    /// Reserved bits and the generation aren't checked, because any of their
+    /// values is possible.
+    ///
+    /// # Errors
+    ///
+    /// * [`InvalidKey::Zero`] if the value is zero.
+    /// * [`InvalidKey::NoPage`] if the value doesn't refer to any page.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{DefaultConfig, Idr, InvalidKey, Key};
+    ///
+    /// let idr = Idr::default();
+    /// let key = idr.insert("foo").unwrap();
+    ///
+    /// let raw = u64::from(key);
+    /// assert_eq!(Key::try_from_raw::<DefaultConfig>(raw), Ok(key));
+    ///
+    /// assert_eq!(Key::try_from_raw::<DefaultConfig>(0), Err(InvalidKey::Zero));
+    /// assert_eq!(Key::try_from_raw::<DefaultConfig>(1), Err(InvalidKey::NoPage));
+    /// ```
+    pub fn try_from_raw<C: Config>(raw: u64) -> Result<Self, InvalidKey> {
+        let key = NonZeroU64::new(raw).map(Self).ok_or(InvalidKey::Zero)?;
+
+        // Slot ids less than the initial page size don't have the page bit.
+        if key.slot_id::<C>() < C::INITIAL_PAGE_SIZE {
+            return Err(InvalidKey::NoPage);
+        }
+
+        Ok(key)
+    }
+
+    /// Decodes the key into its parts according to the provided configuration.
+    ///
+    /// The key is decoded as is, without any validation. Use
+    /// [`Key::try_from_raw()`] to check keys received from untrusted sources.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{DefaultConfig, Idr};
+    ///
+    /// let idr = Idr::default();
+    /// let key = idr.insert("foo").unwrap();
+    ///
+    /// let parts = key.decode::<DefaultConfig>();
+    /// assert_eq!(parts.reserved, 0);
+    /// assert_eq!(parts.generation, 0);
+    /// assert_eq!(parts.page_no, Some(0));
+    /// assert_eq!(parts.slot_index, Some(0));
+    /// ```
+    #[must_use]
+    pub fn decode<C: Config>(self) -> KeyParts {
+        let slot_id = self.slot_id::<C>();
+
+        let (page_no, slot_index) = if slot_id < C::INITIAL_PAGE_SIZE {
+            (None, None)
+        } else {
+            let page_no = self.page_no::<C>();
+            let slot_index = slot_id - page_no.start_slot_id();
+            (Some(page_no.to_u32()), Some(slot_index))
+        };
+
+        KeyParts {
+            reserved: self.reserved::<C>(),
+            generation: self.generation::<C>().to_u32() & C::GENERATION_MASK,
+            page_no,
+            slot_index,
+        }
+    }
+
+    /// Returns reserved bits of the key, see [`Config::RESERVED_BITS`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Config, Idr};
+    ///
+    /// struct MyConfig;
+    /// impl Config for MyConfig {
+    ///     const RESERVED_BITS: u32 = 8;
+    /// }
+    ///
+    /// let idr = Idr::<_, MyConfig>::new();
+    /// let key = idr.insert_with_reserved("foo", 42).unwrap();
+    /// assert_eq!(key.reserved::<MyConfig>(), 42);
+    /// ```
+    #[must_use]
+    pub fn reserved<C: Config>(self) -> u32 {
+        // It never truncates, because `RESERVED_BITS <= 32`.
+        self.0.get().checked_shr(C::USED_BITS).unwrap_or(0) as u32
+    }
+
+    /// Returns the key with reserved bits replaced by the provided ones,
+    /// see [`Config::RESERVED_BITS`].
+    ///
+    /// Unless [`Config::STRICT_RESERVED_BITS`] is enabled, the IDR ignores
+    /// reserved bits, so the returned key refers to the same entry.
+    ///
+    /// # Panics
+    ///
+    /// * If `bits` doesn't fit into [`Config::RESERVED_BITS`].
+    /// * If the key consists only of reserved bits, it's impossible for keys
+    ///   returned by the IDR or validated by [`Key::try_from_raw()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Config, EbrGuard, Idr};
+    ///
+    /// struct MyConfig;
+    /// impl Config for MyConfig {
+    ///     const RESERVED_BITS: u32 = 8;
+    /// }
+    ///
+    /// let idr = Idr::<_, MyConfig>::new();
+    /// let key = idr.insert("foo").unwrap();
+    ///
+    /// let tagged = key.with_reserved::<MyConfig>(42);
+    /// assert_ne!(tagged, key);
+    /// assert_eq!(tagged.reserved::<MyConfig>(), 42);
+    /// assert_eq!(idr.get(tagged, &EbrGuard::new()).unwrap(), "foo");
+    /// ```
+    #[must_use]
+    pub fn with_reserved<C: Config>(self, bits: u32) -> Self {
+        assert!(
+            bits.checked_shr(C::RESERVED_BITS).unwrap_or(0) == 0,
+            "reserved bits don't fit into RESERVED_BITS"
+        );
+
+        let used = self.0.get() & u64::MAX >> C::RESERVED_BITS;
+        let raw = used | u64::from(bits).checked_shl(C::USED_BITS).unwrap_or(0);
+        Self(NonZeroU64::new(raw).expect("the key consists only of reserved bits"))
+    }
+
     /// # Safety
     ///
     /// Both parameters cannot be zero.
@@ -72,8 +225,10 @@ impl Key {
         self.0.get() as u32 & C::SLOT_MASK
     }
 
+    /// Returns the generation, which also contains reserved bits in the strict
+    /// mode and with instance tags, see [`Config::STRICT_RESERVED_BITS`].
     pub(crate) fn generation<C: Config>(self) -> Generation<C> {
-        let gen = (self.0.get() >> C::SLOT_BITS) as u32 & C::GENERATION_MASK;
+        let gen = (self.0.get() >> C::SLOT_BITS) as u32 & C::STORED_GENERATION_MASK;
         Generation::new(gen)
     }
 }
@@ -104,6 +259,213 @@ impl From<Key> for u64 {
     }
 }
 
+// === IdrKey ===
+
+/// A type of keys of the IDR, see the `K` parameter of [`Idr`].
+///
+/// It allows using distinct key types for different IDRs, so that a key
+/// of one IDR cannot be passed to another one by mistake. All key types have
+/// the same layout as [`Key`], they only differ at the type level.
+///
+/// It's implemented for [`Key`] (the default) and [`TypedKey`], but it also
+/// can be implemented for custom newtypes.
+///
+/// # Example
+///
+/// ```
+/// use idr_ebr::{DefaultConfig, EbrGuard, Global, Idr, IdrKey, Key};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// struct UserId(Key);
+///
+/// impl IdrKey for UserId {
+///     fn from_key(key: Key) -> Self {
+///         Self(key)
+///     }
+///
+///     fn into_key(self) -> Key {
+///         self.0
+///     }
+/// }
+///
+/// let users = Idr::<_, DefaultConfig, Global, UserId>::new();
+/// let id: UserId = users.insert("alice").unwrap();
+/// assert_eq!(users.get(id, &EbrGuard::new()).unwrap(), "alice");
+/// ```
+///
+/// [`Idr`]: crate::Idr
+pub trait IdrKey: Copy {
+    /// Converts a raw key into this type.
+    ///
+    /// It's used to wrap keys issued by the IDR, but it's also safe to call
+    /// for any key: the IDR checks keys on access anyway.
+    fn from_key(key: Key) -> Self;
+
+    /// Converts this type into a raw key.
+    fn into_key(self) -> Key;
+}
+
+impl IdrKey for Key {
+    #[inline]
+    fn from_key(key: Key) -> Self {
+        key
+    }
+
+    #[inline]
+    fn into_key(self) -> Key {
+        self
+    }
+}
+
+// === TypedKey ===
+
+/// A key bound to the type of values, see [`IdrKey`].
+///
+/// IDRs with `TypedKey<T>` accept and return only keys of their own value
+/// type, so a key of `Idr<User>` cannot be used with `Idr<Session>`.
+///
+/// # Example
+///
+/// ```
+/// use idr_ebr::{DefaultConfig, Global, Idr, TypedKey};
+///
+/// struct User(&'static str);
+/// struct Session(u64);
+///
+/// let users = Idr::<User, DefaultConfig, Global, TypedKey<User>>::new();
+/// let sessions = Idr::<Session, DefaultConfig, Global, TypedKey<Session>>::new();
+///
+/// let user_id = users.insert(User("alice")).unwrap();
+/// let session_id = sessions.insert(Session(42)).unwrap();
+///
+/// assert!(users.contains(user_id));
+/// assert!(sessions.contains(session_id));
+/// ```
+///
+/// Keys of another IDR are rejected at compile time:
+///
+/// ```compile_fail
+/// use idr_ebr::{DefaultConfig, Global, Idr, TypedKey};
+///
+/// struct User(&'static str);
+/// struct Session(u64);
+///
+/// let users = Idr::<User, DefaultConfig, Global, TypedKey<User>>::new();
+/// let sessions = Idr::<Session, DefaultConfig, Global, TypedKey<Session>>::new();
+///
+/// let user_id = users.insert(User("alice")).unwrap();
+/// sessions.contains(user_id);
+/// ```
+#[repr(transparent)]
+pub struct TypedKey<T> {
+    key: Key,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T> IdrKey for TypedKey<T> {
+    #[inline]
+    fn from_key(key: Key) -> Self {
+        Self {
+            key,
+            _value: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn into_key(self) -> Key {
+        self.key
+    }
+}
+
+impl<T> Copy for TypedKey<T> {}
+
+impl<T> Clone for TypedKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> PartialEq for TypedKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<T> Eq for TypedKey<T> {}
+
+impl<T> PartialOrd for TypedKey<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for TypedKey<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl<T> Hash for TypedKey<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for TypedKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedKey").field(&self.key.0).finish()
+    }
+}
+
+impl<T> From<TypedKey<T>> for Key {
+    fn from(key: TypedKey<T>) -> Key {
+        key.key
+    }
+}
+
+impl<T> From<TypedKey<T>> for NonZeroU64 {
+    fn from(key: TypedKey<T>) -> NonZeroU64 {
+        key.key.0
+    }
+}
+
+impl<T> From<TypedKey<T>> for u64 {
+    fn from(key: TypedKey<T>) -> u64 {
+        key.key.0.get()
+    }
+}
+
+// === Instance tag ===
+
+/// Returns a random non-zero tag, which fits into reserved bits,
+/// see [`Config::INSTANCE_TAG`].
+pub(crate) fn random_tag<C: Config>() -> u32 {
+    // Every `RandomState` is seeded differently, so it's enough to detect
+    // keys of other instances, no need for a cryptographically secure RNG.
+    let random = RandomState::new().build_hasher().finish();
+    let max = (1u64 << C::RESERVED_BITS) - 1;
+
+    // It never truncates, because `RESERVED_BITS <= 32`.
+    (random % max + 1) as u32
+}
+
+// === KeyParts ===
+
+/// Parts of a key, see [`Key::decode()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct KeyParts {
+    /// Reserved bits, see [`Config::RESERVED_BITS`].
+    pub reserved: u32,
+    /// The generation of the slot when the key was issued.
+    pub generation: u32,
+    /// The number of the page, or `None` if the key doesn't refer to any page.
+    pub page_no: Option<u32>,
+    /// The index of the slot within the page,
+    /// or `None` if the key doesn't refer to any page.
+    pub slot_index: Option<u32>,
+}
+
 // === PageNo ===
 
 #[repr(transparent)]
@@ -132,6 +494,10 @@ impl<C: Config> PageNo<C> {
         self.value as usize
     }
 
+    pub(crate) fn to_u32(self) -> u32 {
+        self.value
+    }
+
     pub(crate) fn start_slot_id(self) -> u32 {
         let shift = C::INITIAL_PAGE_SIZE.trailing_zeros() + self.value;
         1 << shift
@@ -150,6 +516,7 @@ impl<C> PartialEq for PageNo<C> {
 
 // === Generation ===
 
+/// In the strict mode, it also contains reserved bits in high-order bits.
 #[repr(transparent)]
 pub(crate) struct Generation<C> {
     value: u32,
@@ -176,9 +543,15 @@ impl<C: Config> Generation<C> {
         self.value
     }
 
+    /// Returns `true` if the next generation wraps around.
+    pub(crate) fn is_last(self) -> bool {
+        self.value & C::GENERATION_MASK == C::GENERATION_MASK
+    }
+
     pub(crate) fn inc(self) -> Self {
         Self {
-            value: (self.value + 1) & C::GENERATION_MASK,
+            // Reserved bits are reset, they're set again on the next insertion.
+            value: self.value.wrapping_add(1) & C::GENERATION_MASK,
             _config: PhantomData,
         }
     }
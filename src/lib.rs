@@ -1,24 +1,44 @@
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 
-use std::fmt;
+use std::{fmt, marker::PhantomData};
 
-use self::{config::ConfigPrivate, control::PageControl, key::PageNo, page::Page};
+use self::{
+    config::ConfigPrivate, control::PageControl, error::AllocError, feed::ChangeFeed,
+    key::random_tag, key::PageNo, page::Page,
+};
 
+mod allocator;
 mod config;
 mod control;
+mod error;
+mod feed;
 mod handles;
 mod key;
 mod loom;
 mod page;
+#[cfg(feature = "rayon")]
+mod parallel;
 mod slot;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod stats;
 
 pub use self::{
-    config::{Config, DefaultConfig},
-    handles::{BorrowedEntry, Iter, OwnedEntry, VacantEntry},
-    key::Key,
+    allocator::{Global, PageAllocator},
+    config::{Config, DefaultConfig, ReusePolicy},
+    error::{InsertAtError, InsertError, InvalidKey, Lagged},
+    feed::{ChangeEvent, Changes},
+    handles::{BorrowedEntry, Drain, IntoIter, Iter, IterMut, IterOwned, OwnedEntry, VacantEntry},
+    key::{IdrKey, Key, KeyParts, TypedKey},
+    stats::{PageStats, Stats},
 };
 
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub use self::parallel::ParIter;
+
 // === Idr ===
 
 /// An IDR (IDentifier Resolver) provides a way to efficiently and concurrently
@@ -26,11 +46,22 @@ pub use self::{
 /// scenarios where you need to quickly find objects based on their ID. This
 /// structure is designed to be highly efficient in terms of both speed and
 /// memory usage.
-pub struct Idr<T, C = DefaultConfig> {
+///
+/// Pages of slots are allocated using the [`PageAllocator`], which is
+/// [`Global`] by default. See [`Idr::new_in()`] to use a custom one.
+///
+/// Keys are of the [`Key`] type by default. Use [`TypedKey`] or another
+/// [`IdrKey`] implementation to prevent mixing up keys of different IDRs.
+pub struct Idr<T, C = DefaultConfig, A: PageAllocator = Global, K: IdrKey = Key> {
     // TODO: flatten
     pages: Box<[Page<T, C>]>,
     // Used to synchronize page allocations.
-    page_control: PageControl,
+    page_control: PageControl<A>,
+    // Disabled unless `Config::CHANGE_FEED_CAPACITY` is set.
+    feed: ChangeFeed,
+    // Zero unless `Config::INSTANCE_TAG` is set.
+    tag: u32,
+    _key: PhantomData<fn() -> K>,
 }
 
 impl<T: 'static> Default for Idr<T> {
@@ -39,7 +70,43 @@ impl<T: 'static> Default for Idr<T> {
     }
 }
 
-impl<T: 'static, C: Config> Idr<T, C> {
+impl<T: 'static, C: Config, K: IdrKey> Idr<T, C, Global, K> {
+    /// Returns a new IDR with the provided configuration parameters.
+    #[must_use]
+    pub fn new() -> Self {
+        // Perform compile-time postmono checks.
+        assert!(C::ENSURE_VALID);
+
+        Self::new_in(Global)
+    }
+
+    /// Returns a new IDR with enough pages allocated to hold at least
+    /// `capacity` entries without allocating new pages.
+    ///
+    /// If `capacity` exceeds the maximum capacity of the IDR,
+    /// all pages are allocated.
+    ///
+    /// See [`Idr::reserve()`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// If the allocator fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::<u64>::with_capacity(100);
+    /// assert!(idr.stats().capacity >= 100);
+    /// ```
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+}
+
+impl<T: 'static, C: Config, A: PageAllocator, K: IdrKey> Idr<T, C, A, K> {
     /// The number of bits in each key which are used by the IDR.
     ///
     /// If other data is packed into the keys returned by [`Idr::insert()`],
@@ -50,14 +117,219 @@ impl<T: 'static, C: Config> Idr<T, C> {
     /// overriding the [`Config::RESERVED_BITS`] constant.
     pub const USED_BITS: u32 = C::USED_BITS;
 
-    /// Returns a new IDR with the provided configuration parameters.
-    pub fn new() -> Self {
+    /// Returns a new IDR with the provided configuration parameters,
+    /// which allocates pages of slots using the provided allocator.
+    ///
+    /// Only pages are allocated using this allocator. Values are stored in
+    /// EBR containers, which are allocated by [`sdd`] using the global one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{DefaultConfig, Global, Idr};
+    ///
+    /// let idr = Idr::<u64, DefaultConfig, _>::new_in(Global);
+    /// assert!(idr.insert(42).is_some());
+    /// ```
+    pub fn new_in(alloc: A) -> Self {
         // Perform compile-time postmono checks.
         assert!(C::ENSURE_VALID);
 
         Self {
             pages: (0..C::MAX_PAGES).map(PageNo::new).map(Page::new).collect(),
-            page_control: PageControl::default(),
+            page_control: PageControl::new(alloc),
+            feed: ChangeFeed::new(C::CHANGE_FEED_CAPACITY),
+            tag: if C::INSTANCE_TAG {
+                random_tag::<C>()
+            } else {
+                0
+            },
+            _key: PhantomData,
+        }
+    }
+
+    /// Returns a new IDR with enough pages allocated to hold at least
+    /// `capacity` entries, which allocates pages using the provided allocator.
+    ///
+    /// See [`Idr::with_capacity()`] and [`Idr::new_in()`] for more details.
+    ///
+    /// # Panics
+    ///
+    /// If the allocator fails.
+    #[must_use]
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        let idr = Self::new_in(alloc);
+        idr.reserve(capacity);
+        idr
+    }
+
+    /// Returns a reference to the allocator used to allocate pages.
+    #[inline]
+    pub fn allocator(&self) -> &A {
+        self.page_control.allocator()
+    }
+
+    /// Returns the instance tag stamped into reserved bits of all keys
+    /// produced by this IDR, or `None` if [`Config::INSTANCE_TAG`] is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Config, EbrGuard, Idr, Key};
+    ///
+    /// struct TaggedConfig;
+    /// impl Config for TaggedConfig {
+    ///     const RESERVED_BITS: u32 = 8;
+    ///     const INSTANCE_TAG: bool = true;
+    /// }
+    ///
+    /// let idr = Idr::<_, TaggedConfig>::new();
+    /// let key = idr.insert("foo").unwrap();
+    /// assert_eq!(Some(key.reserved::<TaggedConfig>()), idr.instance_tag());
+    ///
+    /// // Keys with another tag, e.g. received as raw `u64`, are rejected.
+    /// let tag = idr.instance_tag().unwrap();
+    /// let foreign = key.with_reserved::<TaggedConfig>(tag % 255 + 1);
+    /// assert!(idr.get(foreign, &EbrGuard::new()).is_none());
+    /// assert!(!idr.remove(foreign));
+    /// assert!(idr.contains(key));
+    /// ```
+    #[inline]
+    pub fn instance_tag(&self) -> Option<u32> {
+        C::INSTANCE_TAG.then_some(self.tag)
+    }
+
+    /// Replaces the random instance tag of a new IDR with the given one,
+    /// e.g. to mirror a primary IDR using [`Idr::insert_at()`] or
+    /// [`Idr::apply()`], which accept only keys with the own instance tag.
+    ///
+    /// # Panics
+    ///
+    /// * If [`Config::INSTANCE_TAG`] is disabled.
+    /// * If the tag is zero or doesn't fit into [`Config::RESERVED_BITS`].
+    /// * If the IDR isn't empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Config, EbrGuard, Idr};
+    ///
+    /// struct TaggedConfig;
+    /// impl Config for TaggedConfig {
+    ///     const RESERVED_BITS: u32 = 8;
+    ///     const INSTANCE_TAG: bool = true;
+    /// }
+    ///
+    /// let primary = Idr::<_, TaggedConfig>::new();
+    /// let key = primary.insert("foo").unwrap();
+    ///
+    /// let tag = primary.instance_tag().unwrap();
+    /// let mut replica = Idr::<_, TaggedConfig>::new().with_instance_tag(tag);
+    /// replica.insert_at(key, "foo").unwrap();
+    /// assert_eq!(replica.get(key, &EbrGuard::new()).unwrap(), "foo");
+    /// ```
+    #[must_use]
+    pub fn with_instance_tag(mut self, tag: u32) -> Self {
+        assert!(C::INSTANCE_TAG, "instance tags are disabled");
+        assert!(
+            tag != 0 && u64::from(tag) < 1u64 << C::RESERVED_BITS,
+            "the tag doesn't fit into RESERVED_BITS"
+        );
+        assert!(self.is_empty(), "the IDR isn't empty");
+
+        self.tag = tag;
+        self
+    }
+
+    /// Allocates enough pages to hold at least `additional` more entries
+    /// without allocating new pages. Thus, following insertions are lock-free
+    /// until `additional` entries are inserted.
+    ///
+    /// Pages are allocated in order, so already allocated pages with free
+    /// slots are taken into account, but the total capacity can exceed the
+    /// requested one, because every next page is twice as large as the
+    /// previous one.
+    ///
+    /// If `additional` exceeds the remaining capacity of the IDR,
+    /// all pages are allocated.
+    ///
+    /// This method blocks while allocating new pages.
+    ///
+    /// # Panics
+    ///
+    /// If the allocator fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::default();
+    /// idr.insert(1).unwrap();
+    ///
+    /// idr.reserve(100);
+    /// assert!(idr.stats().free >= 100);
+    /// ```
+    pub fn reserve(&self, additional: usize) {
+        let mut free = 0;
+
+        for page in &*self.pages {
+            if free >= additional {
+                break;
+            }
+
+            page.get_or_allocate(&self.page_control)
+                .expect("failed to allocate memory");
+            free += page.free() as usize;
+        }
+    }
+
+    /// Deallocates empty pages at the end of the IDR, returning memory
+    /// to the allocator.
+    ///
+    /// Pages are allocated in order and deallocated in reverse order. Thus,
+    /// only trailing pages without entries are deallocated, and a single entry
+    /// in the last allocated page prevents shrinking at all.
+    ///
+    /// Generations of slots are preserved (it takes 4 bytes per slot), so keys
+    /// issued before shrinking cannot resolve into entries inserted after
+    /// the page is allocated again. If no entries have ever been removed from
+    /// a page, no memory is required for this.
+    ///
+    /// Pages with retired slots are never deallocated, because generations of
+    /// retired slots must be kept, see [`Config::RETIRE_SLOTS_ON_WRAP`].
+    ///
+    /// This method requires exclusive access to the IDR, because slots can be
+    /// accessed without an [`EbrGuard`] (e.g. by [`VacantEntry`]). For the same
+    /// reason, there is no concurrent variant retiring slots through EBR: it
+    /// would require a guard for every access to slots, including insertions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let mut idr = Idr::default();
+    /// let keys = (0..100).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    /// assert_eq!(idr.stats().allocated_pages, 3);
+    ///
+    /// for key in &keys[1..] {
+    ///     idr.remove(*key);
+    /// }
+    ///
+    /// idr.shrink_to_fit();
+    /// assert_eq!(idr.stats().allocated_pages, 1);
+    /// assert_eq!(idr.get(keys[0], &EbrGuard::new()).unwrap(), 0);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        for page in self.pages.iter_mut().rev() {
+            if page.used() != 0 || page.retired() != 0 {
+                break;
+            }
+
+            if page.deallocate(self.page_control.allocator()) {
+                self.page_control.deallocated();
+            }
         }
     }
 
@@ -66,14 +338,18 @@ impl<T: 'static, C: Config> Idr<T, C> {
     ///
     /// This method is, usually, lock-free. However, it can block if a new page
     /// should be allocated. Thus, it can block max [`Config::MAX_PAGES`] times.
-    /// Once allocated, the page is never deallocated until the IDR is dropped.
+    /// Once allocated, the page is deallocated only by [`Idr::shrink_to_fit()`]
+    /// or when the IDR is dropped. Use [`Idr::with_capacity()`] or
+    /// [`Idr::reserve()`] to allocate pages in advance.
     ///
     /// Returns `None` if there is no more space in the IDR,
     /// and no items can be added until some are removed.
+    /// Use [`Idr::try_insert()`] to get the value back in this case.
     ///
     /// # Panics
     ///
     /// If a new page should be allocated, but the allocator fails.
+    /// Use [`Idr::try_insert()`] to handle it.
     ///
     /// # Example
     ///
@@ -85,7 +361,7 @@ impl<T: 'static, C: Config> Idr<T, C> {
     /// assert_eq!(idr.get(key, &EbrGuard::new()).unwrap(), "foo");
     /// ```
     #[inline]
-    pub fn insert(&self, value: T) -> Option<Key> {
+    pub fn insert(&self, value: T) -> Option<K> {
         self.vacant_entry().map(|entry| {
             let key = entry.key();
             entry.insert(value);
@@ -93,11 +369,222 @@ impl<T: 'static, C: Config> Idr<T, C> {
         })
     }
 
+    /// Inserts a value into the IDR with the provided reserved bits in the
+    /// returned key, see [`Config::RESERVED_BITS`].
+    ///
+    /// If [`Config::STRICT_RESERVED_BITS`] is enabled, reserved bits are also
+    /// stored in the slot, and only keys with the same reserved bits can be
+    /// used to access the entry. Otherwise, it's the same as
+    /// `idr.insert(value).map(|key| key.with_reserved::<C>(bits))`.
+    ///
+    /// This method is, usually, lock-free. However, it can block if a new page
+    /// should be allocated. See [`Idr::insert()`] for details.
+    ///
+    /// Returns `None` if there is no more space in the IDR,
+    /// and no items can be added until some are removed.
+    ///
+    /// # Panics
+    ///
+    /// * If `bits` doesn't fit into [`Config::RESERVED_BITS`].
+    /// * If [`Config::INSTANCE_TAG`] is enabled, because reserved bits
+    ///   are occupied by the instance tag.
+    /// * If a new page should be allocated, but the allocator fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Config, EbrGuard, Idr};
+    ///
+    /// struct StrictConfig;
+    /// impl Config for StrictConfig {
+    ///     const RESERVED_BITS: u32 = 8;
+    ///     const STRICT_RESERVED_BITS: bool = true;
+    /// }
+    ///
+    /// let idr = Idr::<_, StrictConfig>::new();
+    /// let key = idr.insert_with_reserved("foo", 42).unwrap();
+    /// assert_eq!(key.reserved::<StrictConfig>(), 42);
+    ///
+    /// let guard = EbrGuard::new();
+    /// assert_eq!(idr.get(key, &guard).unwrap(), "foo");
+    ///
+    /// // Keys with other reserved bits are rejected.
+    /// let forged = key.with_reserved::<StrictConfig>(17);
+    /// assert!(idr.get(forged, &guard).is_none());
+    /// assert!(!idr.remove(forged));
+    /// ```
+    #[inline]
+    pub fn insert_with_reserved(&self, value: T, bits: u32) -> Option<K> {
+        assert!(
+            !C::INSTANCE_TAG,
+            "reserved bits are occupied by the instance tag"
+        );
+
+        self.vacant_entry().map(|mut entry| {
+            entry.set_reserved(bits);
+            let key = entry.key();
+            entry.insert(value);
+            key
+        })
+    }
+
+    /// Inserts a value into the IDR at the given key, e.g. issued by another
+    /// IDR with the same config, when replaying a log or mirroring a primary.
+    ///
+    /// The generation of the slot is taken from the key, so the key resolves
+    /// into the inserted entry, but keys of other generations don't. If
+    /// [`Config::STRICT_RESERVED_BITS`] is enabled, reserved bits are stored too.
+    ///
+    /// Unlike other insertion methods, this one requires exclusive access,
+    /// because the slot should be unlinked from the lock-free list of free
+    /// slots. It takes linear time in the number of free slots in the page.
+    ///
+    /// Pages are allocated in order, so all pages up to the page of the key
+    /// are allocated. A single key of the last page allocates the whole IDR,
+    /// so keys from untrusted sources should be checked in advance.
+    ///
+    /// If [`Config::INSTANCE_TAG`] is enabled, only keys with the instance tag
+    /// of this IDR are accepted. So, to mirror another IDR, its tag must be
+    /// adopted by [`Idr::with_instance_tag()`].
+    ///
+    /// # Errors
+    ///
+    /// * [`InsertAtError::InvalidKey`] if the key doesn't refer to any slot,
+    ///   or if it has another instance tag, see [`Config::INSTANCE_TAG`].
+    /// * [`InsertAtError::Occupied`] if the slot is occupied or retired,
+    ///   see [`Config::RETIRE_SLOTS_ON_WRAP`].
+    /// * [`InsertAtError::AllocFailed`] if a new page should be allocated,
+    ///   but the allocator fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{EbrGuard, Idr, InsertAtError};
+    ///
+    /// let primary = Idr::default();
+    /// let key = primary.insert("foo").unwrap();
+    /// assert!(primary.remove(key));
+    /// let key = primary.insert("bar").unwrap();
+    ///
+    /// let mut replica = Idr::default();
+    /// replica.insert_at(key, "bar").unwrap();
+    /// assert_eq!(replica.get(key, &EbrGuard::new()).unwrap(), "bar");
+    ///
+    /// let error = replica.insert_at(key, "baz").unwrap_err();
+    /// assert_eq!(error, InsertAtError::Occupied("baz"));
+    ///
+    /// // The slot isn't reused by other insertions.
+    /// assert_ne!(replica.insert("qux").unwrap(), key);
+    /// ```
+    pub fn insert_at(&mut self, key: K, value: T) -> Result<(), InsertAtError<T>> {
+        let key = key.into_key();
+        if Key::try_from_raw::<C>(key.into()).is_err() || !self.owns(key) {
+            return Err(InsertAtError::InvalidKey(value));
+        }
+
+        let page_no = key.page_no::<C>();
+        if self.allocate_pages(page_no).is_err() {
+            return Err(InsertAtError::AllocFailed(value));
+        }
+
+        self.pages[page_no.to_usize()]
+            .insert_at(key, value)
+            .map_err(InsertAtError::Occupied)?;
+
+        self.feed.record(ChangeEvent::Inserted(key));
+        Ok(())
+    }
+
+    /// Allocates pages up to the given one (inclusive).
+    /// Pages are allocated in order, so previous pages are allocated too.
+    fn allocate_pages(&self, page_no: PageNo<C>) -> Result<(), AllocError> {
+        for page in &self.pages[..=page_no.to_usize()] {
+            page.get_or_allocate(&self.page_control)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the page of the key, or `None` if the key cannot belong
+    /// to this IDR.
+    #[inline]
+    fn page(&self, key: Key) -> Option<&Page<T, C>> {
+        if !self.owns(key) {
+            return None;
+        }
+
+        self.pages.get(key.page_no::<C>().to_usize())
+    }
+
+    /// Checks the instance tag of the key, see [`Config::INSTANCE_TAG`].
+    #[inline]
+    fn owns(&self, key: Key) -> bool {
+        !C::INSTANCE_TAG || key.reserved::<C>() == self.tag
+    }
+
+    /// Stamps the instance tag into the key, see [`Config::INSTANCE_TAG`].
+    #[inline]
+    fn stamp(&self, key: Key) -> Key {
+        if C::INSTANCE_TAG {
+            key.with_reserved::<C>(self.tag)
+        } else {
+            key
+        }
+    }
+
+    /// Inserts a value into the IDR, returning the key at which that
+    /// value was inserted, or the value back if it cannot be inserted.
+    ///
+    /// Unlike [`Idr::insert()`], this method doesn't drop the value if there
+    /// is no more space in the IDR, and doesn't panic if the allocator fails.
+    ///
+    /// This method is, usually, lock-free. However, it can block if a new page
+    /// should be allocated. Thus, it can block max [`Config::MAX_PAGES`] times.
+    ///
+    /// # Errors
+    ///
+    /// * [`InsertError::Full`] if there is no more space in the IDR,
+    ///   and no items can be added until some are removed.
+    /// * [`InsertError::AllocFailed`] if a new page should be allocated,
+    ///   but the allocator fails. The next call will try to allocate it again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Config, Idr, InsertError};
+    ///
+    /// struct TinyConfig;
+    /// impl Config for TinyConfig {
+    ///     const INITIAL_PAGE_SIZE: u32 = 1;
+    ///     const MAX_PAGES: u32 = 1;
+    ///     const RESERVED_BITS: u32 = 32;
+    /// }
+    ///
+    /// let idr = Idr::<_, TinyConfig>::new();
+    /// assert!(idr.try_insert("foo").is_ok());
+    ///
+    /// let error = idr.try_insert("bar").unwrap_err();
+    /// assert!(matches!(error, InsertError::Full(_)));
+    /// assert_eq!(error.into_inner(), "bar");
+    /// ```
+    #[inline]
+    pub fn try_insert(&self, value: T) -> Result<K, InsertError<T>> {
+        match self.try_vacant_entry() {
+            Ok(entry) => {
+                let key = entry.key();
+                entry.insert(value);
+                Ok(key)
+            }
+            Err(error) => Err(error.with_value(value)),
+        }
+    }
+
     /// Returns a handle to a vacant entry allowing for further manipulation.
     ///
     /// This method is, usually, lock-free. However, it can block if a new page
     /// should be allocated. Thus, it can block max [`Config::MAX_PAGES`] times.
-    /// Once allocated, the page is never deallocated until the IDR is dropped.
+    /// Once allocated, the page is deallocated only by [`Idr::shrink_to_fit()`]
+    /// or when the IDR is dropped. Use [`Idr::with_capacity()`] or
+    /// [`Idr::reserve()`] to allocate pages in advance.
     ///
     /// This method is useful when creating values that must contain their
     /// IDR key. The returned [`VacantEntry`] reserves a slot in the IDR and
@@ -109,6 +596,7 @@ impl<T: 'static, C: Config> Idr<T, C> {
     /// # Panics
     ///
     /// If a new page should be allocated, but the allocator fails.
+    /// Use [`Idr::try_vacant_entry()`] to handle it.
     ///
     /// # Example
     ///
@@ -128,10 +616,125 @@ impl<T: 'static, C: Config> Idr<T, C> {
     /// assert_eq!(idr.get(key, &EbrGuard::new()).unwrap().1, "foo");
     /// ```
     #[inline]
-    pub fn vacant_entry(&self) -> Option<VacantEntry<'_, T, C>> {
+    pub fn vacant_entry(&self) -> Option<VacantEntry<'_, T, C, K>> {
+        self.reserve_entry()
+            .map(|result| result.expect("failed to allocate memory"))
+    }
+
+    /// Returns a handle to a vacant entry allowing for further manipulation,
+    /// or an error if a slot cannot be reserved.
+    ///
+    /// Unlike [`Idr::vacant_entry()`], this method doesn't panic if the
+    /// allocator fails.
+    ///
+    /// This method is, usually, lock-free. However, it can block if a new page
+    /// should be allocated. Thus, it can block max [`Config::MAX_PAGES`] times.
+    ///
+    /// # Errors
+    ///
+    /// * [`InsertError::Full`] if there is no more space in the IDR,
+    ///   and no items can be added until some are removed.
+    /// * [`InsertError::AllocFailed`] if a new page should be allocated,
+    ///   but the allocator fails. The next call will try to allocate it again.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let idr = Idr::default();
+    ///
+    /// let entry = idr.try_vacant_entry().unwrap();
+    /// let key = entry.key();
+    /// entry.insert((key, "foo"));
+    ///
+    /// assert_eq!(idr.get(key, &EbrGuard::new()).unwrap().1, "foo");
+    /// ```
+    #[inline]
+    pub fn try_vacant_entry(&self) -> Result<VacantEntry<'_, T, C, K>, InsertError> {
+        match self.reserve_entry() {
+            Some(Ok(entry)) => Ok(entry),
+            Some(Err(AllocError)) => Err(InsertError::AllocFailed(())),
+            None => Err(InsertError::Full(())),
+        }
+    }
+
+    /// Inserts values into the IDR, returning keys at which they were
+    /// inserted, in the same order.
+    ///
+    /// It's faster than calling [`Idr::insert()`] in a loop, because free
+    /// slots are reserved in chains (up to the lower bound of the iterator's
+    /// size hint) with a single atomic operation where possible.
+    ///
+    /// If the IDR becomes full, remaining values are dropped, and the returned
+    /// vector is shorter than the number of provided values.
+    ///
+    /// This method is, usually, lock-free. However, it can block if new pages
+    /// should be allocated. See [`Idr::insert()`] for details.
+    ///
+    /// # Panics
+    ///
+    /// If the allocator fails.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let idr = Idr::default();
+    /// let keys = idr.insert_many(0..100);
+    /// assert_eq!(keys.len(), 100);
+    ///
+    /// let guard = EbrGuard::new();
+    /// for (i, key) in keys.into_iter().enumerate() {
+    ///     assert_eq!(idr.get(key, &guard).unwrap(), i);
+    /// }
+    /// ```
+    pub fn insert_many(&self, values: impl IntoIterator<Item = T>) -> Vec<K> {
+        let mut values = values.into_iter();
+        let mut keys = Vec::with_capacity(values.size_hint().0);
+        let mut next_value = values.next();
+
+        while next_value.is_some() {
+            let max = values.size_hint().0.saturating_add(1);
+            let max = u32::try_from(max).unwrap_or(u32::MAX);
+
+            let Some(reserved) = self.page_control.choose(&self.pages, |page| {
+                page.reserve_many(&self.page_control, max).transpose()
+            }) else {
+                // The IDR is full.
+                break;
+            };
+
+            let mut reserved = reserved.expect("failed to allocate memory");
+
+            while let Some(value) = next_value.take() {
+                let Some((key, slot)) = reserved.next() else {
+                    next_value = Some(value);
+                    break;
+                };
+
+                let key = self.stamp(key);
+                slot.init(key, value);
+                self.feed.record(ChangeEvent::Inserted(key));
+                keys.push(K::from_key(key));
+                next_value = values.next();
+            }
+        }
+
+        keys
+    }
+
+    /// Reserves a slot, returning `None` if there are no free slots.
+    fn reserve_entry(&self) -> Option<Result<VacantEntry<'_, T, C, K>, AllocError>> {
         self.page_control.choose(&self.pages, |page| {
             page.reserve(&self.page_control)
-                .map(|(key, slot)| VacantEntry::new(page, slot, key))
+                .map(|reserved| {
+                    reserved.map(|(key, slot)| {
+                        VacantEntry::new(page, slot, self.stamp(key), &self.feed)
+                    })
+                })
+                .transpose()
         })
     }
 
@@ -174,30 +777,288 @@ impl<T: 'static, C: Config> Idr<T, C> {
     /// drop(guard);
     /// ```
     #[inline]
-    pub fn remove(&self, key: Key) -> bool {
-        let page_no = key.page_no::<C>();
-        self.pages
-            .get(page_no.to_usize())
-            .map_or(false, |page| page.remove(key))
+    pub fn remove(&self, key: K) -> bool {
+        let key = key.into_key();
+
+        // For now, `impl Drop for Shared` uses a special guard, which doesn't clean up.
+        // It can cause OOM if a thread is alive for a long time and doesn't use a
+        // normal guard via `Idr::get()` or directly (see `insert_remove` benchmark).
+        // TODO: create an issue in sdd. However, it's still required for `get()`.
+        let guard = EbrGuard::new();
+
+        self.page(key)
+            .is_some_and(|page| page.remove(key, &guard, &self.feed))
     }
 
-    /// Returns a borrowed handle to the entry associated with the given key,
-    /// or `None` if the IDR contains no entry for the given key.
+    /// Removes the entry at the given key in the IDR, returning an owned handle
+    /// to the removed entry if a value was present at the moment of the removal.
     ///
-    /// This method is wait-free.
+    /// This method is lock-free.
     ///
-    /// While the handle exists, it indicates to the IDR that the entry the
-    /// handle references is currently being accessed. If the entry is
-    /// removed from the IDR while a handle exists, it's still accessible via
-    /// the handle.
+    /// Unlike calling [`Idr::get_owned()`] and then [`Idr::remove()`], it's
+    /// atomic: if several threads take the same entry concurrently, only one
+    /// of them gets the handle, others get `None`.
     ///
-    /// This method **doesn't modify memory**, thus it creates no contention on
-    /// it at all. This is the whole point of the EBR pattern and the reason
-    /// why it's used here.
+    /// The removed entry becomes unreachable for getting instantly,
+    /// but it still can be accessed using the returned and existing handles.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::default();
+    /// let key = idr.insert("foo").unwrap();
+    ///
+    /// let entry = idr.take(key).unwrap();
+    /// assert_eq!(entry, "foo");
+    ///
+    /// // Now, the entry is unrechable using IDR.
+    /// assert!(!idr.contains(key));
+    ///
+    /// // Repeat taking will return None.
+    /// assert!(idr.take(key).is_none());
+    /// ```
+    #[inline]
+    pub fn take(&self, key: K) -> Option<OwnedEntry<T>> {
+        let key = key.into_key();
+        let page = self.page(key)?;
+        page.take(key, &EbrGuard::new(), &self.feed)
+            .map(OwnedEntry::new)
+    }
+
+    /// Removes entries at the given keys in the IDR, returning the number of
+    /// values that were present at the moment of the removal.
+    ///
+    /// This method is lock-free.
+    ///
+    /// It's faster than calling [`Idr::remove()`] in a loop, because all
+    /// entries are removed under a single [`EbrGuard`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::default();
+    /// let keys = idr.insert_many(0..10);
+    ///
+    /// assert_eq!(idr.remove_many(&keys[..5]), 5);
+    /// assert_eq!(idr.remove_many(&keys), 5);
+    /// assert!(idr.is_empty());
+    /// ```
+    pub fn remove_many(&self, keys: &[K]) -> usize {
+        // See `Idr::remove()` for details.
+        let guard = EbrGuard::new();
+
+        keys.iter()
+            .filter(|key| {
+                let key = key.into_key();
+                self.page(key)
+                    .is_some_and(|page| page.remove(key, &guard, &self.feed))
+            })
+            .count()
+    }
+
+    /// Retains only the entries for which the predicate returns `true`,
+    /// removing the rest.
+    ///
+    /// This method is lock-free. Pages are walked once under a single
+    /// [`EbrGuard`], so it's cheaper than calling [`Idr::remove()`] for
+    /// every key collected by [`Idr::iter()`].
+    ///
+    /// It's safe to call concurrently with other operations. In this case:
+    /// * Every entry present during the whole pass is visited exactly once.
+    /// * Entries inserted during the pass may or may not be visited.
+    /// * An entry is removed only if it still contains the checked value.
+    ///   If it's replaced (or removed and inserted again) after the check,
+    ///   the new value is kept.
+    ///
+    /// Removed entries behave as in [`Idr::remove()`], i.e. they still can be
+    /// accessed using existing handles.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::default();
+    /// let keys = (0..10).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    ///
+    /// idr.retain(|_, value| value % 2 == 0);
+    /// assert_eq!(idr.len(), 5);
+    /// assert!(idr.contains(keys[0]));
+    /// assert!(!idr.contains(keys[1]));
+    /// ```
+    pub fn retain(&self, mut f: impl FnMut(K, &T) -> bool) {
+        let guard = EbrGuard::new();
+        let mut f = |key, value: &T| f(K::from_key(key), value);
+
+        for page in &*self.pages {
+            page.retain(&mut f, &guard, &self.feed);
+        }
+    }
+
+    /// Removes all entries from the IDR. Allocated pages are kept.
+    ///
+    /// This method is lock-free. It's safe to call concurrently with other
+    /// operations, but entries inserted during the call may be kept.
+    /// See [`Idr::retain()`] for details.
+    ///
+    /// Use [`Idr::drain()`] if the IDR is borrowed exclusively
+    /// and removed values are needed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::default();
+    /// let key = idr.insert("foo").unwrap();
+    ///
+    /// idr.clear();
+    /// assert!(idr.is_empty());
+    /// assert!(!idr.contains(key));
+    /// ```
+    #[inline]
+    pub fn clear(&self) {
+        self.retain(|_, _| false);
+    }
+
+    /// Replaces the value of the entry at the given key in the IDR without
+    /// changing the key, returning an owned handle to the replaced value if
+    /// the entry was present at the moment of the replacement.
+    ///
+    /// This method is lock-free.
+    ///
+    /// If there is no entry for the given key, the provided value is dropped.
+    ///
+    /// The replaced value is still accessible using existing handles, and it's
+    /// not actually dropped until all handles are dropped and EBR garbage is
+    /// cleaned up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let idr = Idr::default();
+    /// let key = idr.insert("foo").unwrap();
+    ///
+    /// let guard = EbrGuard::new();
+    /// let entry = idr.get(key, &guard).unwrap();
+    ///
+    /// assert_eq!(idr.replace(key, "bar").unwrap(), "foo");
+    /// assert_eq!(idr.get(key, &guard).unwrap(), "bar");
+    ///
+    /// // Existing handles still refer to the old value.
+    /// assert_eq!(entry, "foo");
+    /// ```
+    #[inline]
+    pub fn replace(&self, key: K, value: T) -> Option<OwnedEntry<T>> {
+        let key = key.into_key();
+        let page = self.page(key)?;
+        let old = page.replace(key, value)?;
+        self.feed.record(ChangeEvent::Replaced(key));
+        Some(OwnedEntry::new(old))
+    }
+
+    /// Replaces the value of the entry at the given key in the IDR only if
+    /// the entry still refers to the same value as `current`, returning an
+    /// owned handle to the replaced value on success.
+    ///
+    /// This method is lock-free.
+    ///
+    /// Returns `None` if the entry has been removed or replaced since `current`
+    /// was obtained. In this case, the provided value is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let idr = Idr::default();
+    /// let key = idr.insert("foo").unwrap();
+    ///
+    /// let guard = EbrGuard::new();
+    /// let current = idr.get(key, &guard).unwrap();
+    ///
+    /// assert_eq!(idr.compare_and_swap(key, &current, "bar").unwrap(), "foo");
+    ///
+    /// // `current` is outdated now.
+    /// assert!(idr.compare_and_swap(key, &current, "baz").is_none());
+    /// assert_eq!(idr.get(key, &guard).unwrap(), "bar");
+    /// ```
+    #[inline]
+    pub fn compare_and_swap(
+        &self,
+        key: K,
+        current: &BorrowedEntry<'_, T>,
+        value: T,
+    ) -> Option<OwnedEntry<T>> {
+        let key = key.into_key();
+        let page = self.page(key)?;
+        let old = page.compare_and_swap(key, current.as_ptr(), value)?;
+        self.feed.record(ChangeEvent::Replaced(key));
+        Some(OwnedEntry::new(old))
+    }
+
+    /// Replaces the value of the entry at the given key in the IDR with a new
+    /// value produced by `f` from the current one, returning an owned handle
+    /// to the replaced value if the entry was present.
+    ///
+    /// This method is lock-free, but not wait-free: if the entry is replaced
+    /// concurrently, `f` is called again with the actual value (RCU).
+    /// Thus, `f` can be called multiple times, and it shouldn't be expensive.
+    ///
+    /// Returns `None` if there is no entry for the given key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let idr = Idr::default();
+    /// let key = idr.insert(vec![1, 2]).unwrap();
+    ///
+    /// let old = idr.update(key, |old| {
+    ///     let mut new = old.clone();
+    ///     new.push(3);
+    ///     new
+    /// });
+    ///
+    /// assert_eq!(old.unwrap(), vec![1, 2]);
+    /// assert_eq!(idr.get(key, &EbrGuard::new()).unwrap(), vec![1, 2, 3]);
+    /// ```
+    #[inline]
+    pub fn update(&self, key: K, f: impl FnMut(&T) -> T) -> Option<OwnedEntry<T>> {
+        let key = key.into_key();
+        let page = self.page(key)?;
+        let old = page.update(key, f)?;
+        self.feed.record(ChangeEvent::Replaced(key));
+        Some(OwnedEntry::new(old))
+    }
+
+    /// Returns a borrowed handle to the entry associated with the given key,
+    /// or `None` if the IDR contains no entry for the given key.
+    ///
+    /// This method is wait-free.
+    ///
+    /// While the handle exists, it indicates to the IDR that the entry the
+    /// handle references is currently being accessed. If the entry is
+    /// removed from the IDR while a handle exists, it's still accessible via
+    /// the handle.
+    ///
+    /// This method **doesn't modify memory**, thus it creates no contention on
+    /// it at all. This is the whole point of the EBR pattern and the reason
+    /// why it's used here.
     ///
     /// The returned handle cannot be send to another thread.
     /// Also, it means it cannot be hold over `.await` points.
     ///
+    /// The returned handle borrows the IDR, because [`Idr::get_mut()`]
+    /// relies on the absence of borrowed handles.
+    ///
     /// # Example
     ///
     /// ```
@@ -218,12 +1079,49 @@ impl<T: 'static, C: Config> Idr<T, C> {
     /// assert!(idr.get(Key::try_from(12345).unwrap(), &guard).is_none());
     /// ```
     #[inline]
-    pub fn get<'g>(&self, key: Key, guard: &'g EbrGuard) -> Option<BorrowedEntry<'g, T>> {
-        let page_no = key.page_no::<C>();
-        let page = self.pages.get(page_no.to_usize())?;
+    pub fn get<'g>(&'g self, key: K, guard: &'g EbrGuard) -> Option<BorrowedEntry<'g, T>> {
+        let key = key.into_key();
+        let page = self.page(key)?;
         page.get(key, guard)
     }
 
+    /// Returns borrowed handles to entries associated with the given keys,
+    /// in the same order. See [`Idr::get()`] for details.
+    ///
+    /// This method is wait-free.
+    ///
+    /// It's faster than calling [`Idr::get()`] in a loop, because slots are
+    /// prefetched into the cache before being accessed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let idr = Idr::default();
+    /// let keys = idr.insert_many(["foo", "bar"]);
+    /// assert!(idr.remove(keys[0]));
+    ///
+    /// let guard = EbrGuard::new();
+    /// let entries = idr.get_many(&keys, &guard);
+    /// assert!(entries[0].is_none());
+    /// assert_eq!(entries[1].unwrap(), "bar");
+    /// ```
+    pub fn get_many<'g>(
+        &'g self,
+        keys: &[K],
+        guard: &'g EbrGuard,
+    ) -> Vec<Option<BorrowedEntry<'g, T>>> {
+        for key in keys {
+            let key = key.into_key();
+            if let Some(page) = self.page(key) {
+                page.prefetch(key);
+            }
+        }
+
+        keys.iter().map(|key| self.get(*key, guard)).collect()
+    }
+
     /// Returns a owned handle to the entry associated with the given key,
     /// or `None` if the IDR contains no entry for the given key.
     ///
@@ -260,7 +1158,7 @@ impl<T: 'static, C: Config> Idr<T, C> {
     /// }).join().unwrap();
     /// ```
     #[inline]
-    pub fn get_owned(&self, key: Key) -> Option<OwnedEntry<T>> {
+    pub fn get_owned(&self, key: K) -> Option<OwnedEntry<T>> {
         self.get(key, &EbrGuard::new())?.to_owned()
     }
 
@@ -282,10 +1180,270 @@ impl<T: 'static, C: Config> Idr<T, C> {
     /// assert!(!idr.contains(key));
     /// ```
     #[inline]
-    pub fn contains(&self, key: Key) -> bool {
+    pub fn contains(&self, key: K) -> bool {
         self.get(key, &EbrGuard::new()).is_some()
     }
 
+    /// Returns the number of entries in the IDR.
+    ///
+    /// This method is wait-free.
+    ///
+    /// Entries reserved by [`Idr::vacant_entry()`], but not inserted yet, are
+    /// also counted.
+    ///
+    /// The counters are updated without any synchronization to avoid extra
+    /// contention. Thus, the returned value is exact only if there are no
+    /// concurrent insertions or removals. Otherwise, it's a snapshot, which
+    /// may or may not take into account concurrent changes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::default();
+    /// assert_eq!(idr.len(), 0);
+    ///
+    /// let key = idr.insert("foo").unwrap();
+    /// idr.insert("bar").unwrap();
+    /// assert_eq!(idr.len(), 2);
+    ///
+    /// idr.remove(key);
+    /// assert_eq!(idr.len(), 1);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.pages.iter().map(|page| page.used() as usize).sum()
+    }
+
+    /// Returns `true` if the IDR contains no entries.
+    ///
+    /// This method is wait-free.
+    ///
+    /// See [`Idr::len()`] for details about accuracy under concurrency.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::default();
+    /// assert!(idr.is_empty());
+    ///
+    /// idr.insert("foo").unwrap();
+    /// assert!(!idr.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pages.iter().all(|page| page.used() == 0)
+    }
+
+    /// Returns the number of retired slots, which are never reused.
+    ///
+    /// Slots are retired only if [`Config::RETIRE_SLOTS_ON_WRAP`] is enabled,
+    /// once their generation would wrap. Every retired slot reduces the
+    /// effective capacity of the IDR by one, and the counter never decreases.
+    ///
+    /// This method is wait-free.
+    ///
+    /// See [`Idr::len()`] for details about accuracy under concurrency.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Config, Idr};
+    ///
+    /// struct MyConfig;
+    /// impl Config for MyConfig {
+    ///     const RESERVED_BITS: u32 = 30;
+    ///     const RETIRE_SLOTS_ON_WRAP: bool = true;
+    /// }
+    ///
+    /// let idr = Idr::<u32, MyConfig>::new();
+    /// let key = idr.insert(1).unwrap();
+    /// assert!(idr.remove(key));
+    /// assert_eq!(idr.retired(), 0);
+    ///
+    /// // There are only 4 generations, the last one retires the slot.
+    /// for _ in 0..3 {
+    ///     let key = idr.insert(2).unwrap();
+    ///     assert!(idr.remove(key));
+    /// }
+    ///
+    /// assert_eq!(idr.retired(), 1);
+    /// assert_eq!(idr.stats().retired, 1);
+    /// assert_ne!(idr.insert(3).unwrap(), key);
+    /// ```
+    #[inline]
+    pub fn retired(&self) -> usize {
+        self.pages.iter().map(|page| page.retired() as usize).sum()
+    }
+
+    /// Returns a snapshot of the IDR's occupancy and memory usage.
+    ///
+    /// This method is wait-free, but it allocates to collect per-page statistics.
+    ///
+    /// The snapshot is consistent only if there are no concurrent insertions
+    /// or removals, see [`Idr::len()`] for details.
+    ///
+    /// Note that the size of EBR containers is estimated for occupied slots
+    /// only. It doesn't include heap memory owned by values and memory of
+    /// removed values, which haven't been reclaimed by EBR yet.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::<u64>::default();
+    /// assert_eq!(idr.stats().allocated_pages, 0);
+    ///
+    /// idr.insert(42).unwrap();
+    ///
+    /// let stats = idr.stats();
+    /// assert_eq!(stats.allocated_pages, 1);
+    /// assert_eq!(stats.capacity, 32);
+    /// assert_eq!(stats.occupied, 1);
+    /// assert_eq!(stats.free, 31);
+    /// assert_eq!(stats.pages[0].occupied, 1);
+    /// ```
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+
+        for (page_no, page) in self.pages.iter().enumerate() {
+            if let Some(page_stats) = page.stats(page_no) {
+                stats.add_page(page_stats);
+            }
+        }
+
+        stats
+    }
+
+    /// Returns a reader of the change feed, which yields events recorded
+    /// after this call. Along with [`Idr::apply()`], it allows mirroring
+    /// the IDR to another one (a follower) with the same keys.
+    ///
+    /// The change feed is disabled by default, see
+    /// [`Config::CHANGE_FEED_CAPACITY`]. If it's disabled, the reader yields
+    /// nothing.
+    ///
+    /// The following operations are recorded:
+    /// * [`ChangeEvent::Inserted`] by [`Idr::insert()`], [`Idr::insert_many()`],
+    ///   [`Idr::insert_at()`], [`VacantEntry::insert()`] and other insertions.
+    /// * [`ChangeEvent::Replaced`] by [`Idr::replace()`],
+    ///   [`Idr::compare_and_swap()`] and [`Idr::update()`].
+    /// * [`ChangeEvent::Removed`] by [`Idr::remove()`], [`Idr::take()`],
+    ///   [`Idr::retain()`], [`Idr::drain()`] and other removals.
+    ///
+    /// Values changed in place by [`Idr::get_mut()`] and [`Idr::iter_mut()`]
+    /// aren't recorded.
+    ///
+    /// Events contain only keys. An event is recorded after the operation
+    /// takes effect, so its value can be read from the IDR by the key, unless
+    /// it has been removed or replaced since. In this case, the corresponding
+    /// event follows. A removal is always recorded before the next insertion
+    /// into the same slot.
+    ///
+    /// The feed is bounded: if the reader lags behind by more than
+    /// `CHANGE_FEED_CAPACITY` events, it yields [`Lagged`] and continues from
+    /// the oldest retained event.
+    ///
+    /// This method is lock-free, as well as reading and recording events.
+    /// However, applying events by [`Idr::apply()`] requires exclusive access
+    /// to the follower, so it cannot serve reads while events are applied.
+    /// If it must, protect it by a lock, e.g. [`std::sync::RwLock`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Config, EbrGuard, Idr};
+    ///
+    /// struct FeedConfig;
+    /// impl Config for FeedConfig {
+    ///     const CHANGE_FEED_CAPACITY: usize = 1024;
+    /// }
+    ///
+    /// let primary = Idr::<_, FeedConfig>::new();
+    /// let mut changes = primary.changes();
+    ///
+    /// let foo = primary.insert("foo").unwrap();
+    /// let bar = primary.insert("bar").unwrap();
+    /// primary.replace(foo, "baz");
+    /// primary.remove(bar);
+    ///
+    /// // Keys of the primary are valid for the follower with the same layout.
+    /// // The follower is borrowed mutably while applying events.
+    /// let mut follower = Idr::default();
+    ///
+    /// for event in changes.by_ref() {
+    ///     let event = event.expect("lagged");
+    ///     let guard = EbrGuard::new();
+    ///     let value = primary.get(event.key(), &guard).map(|entry| *entry);
+    ///     follower.apply(event, value).unwrap();
+    /// }
+    ///
+    /// let guard = EbrGuard::new();
+    /// assert_eq!(follower.get(foo, &guard).unwrap(), "baz");
+    /// assert!(!follower.contains(bar));
+    /// ```
+    #[inline]
+    pub fn changes(&self) -> Changes<'_> {
+        Changes::new(&self.feed)
+    }
+
+    /// Applies an event from the change feed of another IDR (a primary),
+    /// so this IDR contains the same entries at the same keys, including
+    /// generations. See [`Idr::changes()`] for details.
+    ///
+    /// `value` is the value read from the primary by the event's key when the
+    /// event is applied, or `None` if there is no entry at the key anymore.
+    ///
+    /// * [`ChangeEvent::Inserted`] inserts the value at the key using
+    ///   [`Idr::insert_at()`], or replaces the value if there is already an
+    ///   entry at the key, e.g. if it was copied before reading the feed.
+    /// * [`ChangeEvent::Replaced`] replaces the value, if there is an entry.
+    /// * [`ChangeEvent::Removed`] removes the entry.
+    ///
+    /// Events without a value, except removals, are ignored, because the entry
+    /// has been removed from the primary since, and the removal follows.
+    ///
+    /// Events must be applied in the same order they are read from the feed.
+    /// If the reader lags, the follower should be synchronized again, e.g. by
+    /// [`Idr::clear()`] and copying entries using [`Idr::insert_at()`].
+    ///
+    /// This method requires exclusive access, because insertions unlink the
+    /// slot of the key from the lock-free list of free slots, see
+    /// [`Idr::insert_at()`]. Thus, the follower cannot be read concurrently
+    /// while events are applied.
+    ///
+    /// # Errors
+    ///
+    /// The same as [`Idr::insert_at()`]. [`InsertAtError::Occupied`] means
+    /// that the slot contains an entry of another generation, so the follower
+    /// is out of sync with the primary.
+    pub fn apply(&mut self, event: ChangeEvent, value: Option<T>) -> Result<(), InsertAtError<T>> {
+        match (event, value) {
+            (ChangeEvent::Inserted(key), Some(value)) => {
+                let key = K::from_key(key);
+                if self.contains(key) {
+                    let _ = self.replace(key, value);
+                    Ok(())
+                } else {
+                    self.insert_at(key, value)
+                }
+            }
+            (ChangeEvent::Replaced(key), Some(value)) => {
+                let _ = self.replace(K::from_key(key), value);
+                Ok(())
+            }
+            (ChangeEvent::Removed(key), _) => {
+                self.remove(K::from_key(key));
+                Ok(())
+            }
+            (ChangeEvent::Inserted(_) | ChangeEvent::Replaced(_), None) => Ok(()),
+        }
+    }
+
     /// Returns a fused iterator over all occupied entries in the IDR.
     /// An order of iteration is not guaranteed. Added during iteration entries
     /// can be observed via the iterator, but it depends on the current position
@@ -331,12 +1489,293 @@ impl<T: 'static, C: Config> Idr<T, C> {
     /// assert_eq!(entry, "baz");
     /// ```
     #[inline]
-    pub fn iter<'g>(&self, guard: &'g EbrGuard) -> Iter<'g, '_, T, C> {
+    pub fn iter<'g>(&'g self, guard: &'g EbrGuard) -> Iter<'g, 'g, T, C, K> {
         Iter::new(&self.pages, guard)
     }
+
+    /// Returns an iterator over entries in the IDR, ordered by keys, which
+    /// doesn't hold an [`EbrGuard`] and yields owned handles.
+    ///
+    /// Unlike [`Idr::iter()`], the returned iterator is [`Send`] and can be
+    /// held across `.await` points. Every call to [`Iterator::next()`] pins
+    /// a new guard for a short time, and the iterator remembers only the slot
+    /// position between calls. Thus, every slot is visited at most once, even
+    /// if entries are removed and inserted concurrently.
+    ///
+    /// Entries inserted during the iteration may or may not be visited,
+    /// depending on the position of the iterator and free slots chosen.
+    ///
+    /// This method is wait-free, but [`Iterator::next()`] modifies the memory
+    /// by incrementing the reference counter of the yielded entry.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let idr = Idr::default();
+    /// let keys = idr.insert_many(["foo", "bar"]);
+    ///
+    /// let mut iter = idr.iter_owned();
+    /// let (key, entry) = iter.next().unwrap();
+    /// assert_eq!(key, keys[0]);
+    ///
+    /// // Still accessible, because it's an owned handle.
+    /// assert!(idr.remove(key));
+    /// assert_eq!(entry, "foo");
+    ///
+    /// assert_eq!(iter.next().unwrap().1, "bar");
+    /// assert!(iter.next().is_none());
+    /// ```
+    #[inline]
+    pub fn iter_owned(&self) -> IterOwned<'_, T, C, K> {
+        IterOwned::new(&self.pages)
+    }
+
+    /// Returns an iterator over entries in the IDR starting from the given
+    /// key (inclusive), ordered by keys.
+    ///
+    /// Only the slot part of the key is used, so the key isn't required to be
+    /// valid anymore. It allows resuming a scan after the [`EbrGuard`] is
+    /// dropped and created again, even if the last visited entry has been
+    /// removed in the meantime. See [`Idr::get_next()`] for an example.
+    ///
+    /// Otherwise, it behaves like [`Idr::iter()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let idr = Idr::default();
+    /// let keys = idr.insert_many(0..10);
+    ///
+    /// let guard = EbrGuard::new();
+    /// let mut iter = idr.iter_from(keys[5], &guard);
+    /// assert_eq!(iter.next().unwrap().0, keys[5]);
+    /// assert_eq!(iter.next_back().unwrap().0, keys[9]);
+    /// assert_eq!(iter.count(), 3);
+    /// ```
+    #[inline]
+    pub fn iter_from<'g>(&'g self, key: K, guard: &'g EbrGuard) -> Iter<'g, 'g, T, C, K> {
+        Iter::from_key(&self.pages, key.into_key(), guard)
+    }
+
+    /// Returns the first entry after the given key (exclusive) in the key
+    /// order, or `None` if there are no such entries.
+    ///
+    /// Only the slot part of the key is used, so the key isn't required to be
+    /// valid anymore. Thus, it's a convenient way to walk through the IDR step
+    /// by step, like `idr_get_next()` in the Linux kernel, but without
+    /// incrementing the key.
+    ///
+    /// This method is wait-free, but it takes `O(n)` time in the worst case.
+    /// Use [`Idr::iter_from()`] to get multiple entries at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let idr = Idr::default();
+    /// let keys = idr.insert_many(0..10);
+    ///
+    /// // Scan the IDR, re-creating the guard on every step.
+    /// let (mut cursor, _) = idr.iter(&EbrGuard::new()).next().unwrap();
+    /// let mut visited = vec![cursor];
+    ///
+    /// while let Some((key, _)) = idr.get_next(cursor, &EbrGuard::new()) {
+    ///     visited.push(key);
+    ///     cursor = key;
+    /// }
+    ///
+    /// assert_eq!(visited, keys);
+    /// ```
+    pub fn get_next<'g>(
+        &'g self,
+        key: K,
+        guard: &'g EbrGuard,
+    ) -> Option<(K, BorrowedEntry<'g, T>)> {
+        let slot_id = key.into_key().slot_id::<C>();
+        self.iter_from(key, guard)
+            .find(|(next_key, _)| next_key.into_key().slot_id::<C>() != slot_id)
+    }
+
+    /// Returns a parallel iterator over entries in the IDR.
+    ///
+    /// Work is split by pages and ranges of slots inside them. Every worker
+    /// pins its own [`EbrGuard`], because guards cannot be sent to another
+    /// thread. Thus, values are yielded as [`OwnedEntry`], and entries removed
+    /// before being visited are skipped.
+    ///
+    /// Entries inserted during the iteration may or may not be visited,
+    /// just like in [`Idr::iter()`]. The iteration isn't ordered by keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    /// use rayon::prelude::*;
+    ///
+    /// let idr = Idr::default();
+    /// idr.insert_many(0..10_000u64);
+    ///
+    /// let sum = idr.par_iter().map(|(_, entry)| *entry).sum::<u64>();
+    /// assert_eq!(sum, (0..10_000).sum());
+    /// ```
+    #[cfg(feature = "rayon")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+    #[inline]
+    pub fn par_iter(&self) -> ParIter<'_, T, C, K>
+    where
+        T: Send + Sync,
+        K: Send,
+    {
+        ParIter::new(&self.pages)
+    }
+
+    /// Returns a mutable reference to the entry associated with the given key,
+    /// or `None` if the IDR contains no entry for the given key.
+    ///
+    /// No [`EbrGuard`] is required, because the IDR is borrowed exclusively.
+    ///
+    /// Also, it returns `None` if the entry is still shared by an outstanding
+    /// [`OwnedEntry`] (e.g. returned by [`Idr::get_owned()`]), because the
+    /// value cannot be mutated while someone else can read it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let mut idr = Idr::default();
+    /// let key = idr.insert(String::from("foo")).unwrap();
+    ///
+    /// idr.get_mut(key).unwrap().push_str("bar");
+    /// assert_eq!(*idr.get(key, &EbrGuard::new()).unwrap(), "foobar");
+    ///
+    /// // The entry is shared by the owned handle.
+    /// let owned = idr.get_owned(key).unwrap();
+    /// assert!(idr.get_mut(key).is_none());
+    ///
+    /// drop(owned);
+    /// assert!(idr.get_mut(key).is_some());
+    /// ```
+    #[inline]
+    pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
+        let key = key.into_key();
+        if !self.owns(key) {
+            return None;
+        }
+
+        let page = self.pages.get_mut(key.page_no::<C>().to_usize())?;
+        page.get_mut(key)
+    }
+
+    /// Returns an iterator over mutable references to entries in the IDR,
+    /// ordered by keys.
+    ///
+    /// No [`EbrGuard`] is required, because the IDR is borrowed exclusively.
+    ///
+    /// Entries still shared by an outstanding [`OwnedEntry`] are **skipped**,
+    /// because they cannot be mutated. Use [`Idr::iter()`] to visit them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::{Idr, EbrGuard};
+    ///
+    /// let mut idr = Idr::default();
+    /// let foo_key = idr.insert(1).unwrap();
+    /// let bar_key = idr.insert(2).unwrap();
+    ///
+    /// for (_, value) in idr.iter_mut() {
+    ///     *value *= 10;
+    /// }
+    ///
+    /// assert_eq!(idr.get(foo_key, &EbrGuard::new()).unwrap(), 10);
+    /// assert_eq!(idr.get(bar_key, &EbrGuard::new()).unwrap(), 20);
+    /// ```
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, C, K> {
+        IterMut::new(&mut self.pages)
+    }
+
+    /// Removes all entries from the IDR, returning them as an iterator
+    /// ordered by keys. Allocated pages are kept.
+    ///
+    /// Values are yielded as [`OwnedEntry`] instead of `T`, even if there are
+    /// no other handles to them. Values are stored in EBR-managed allocations,
+    /// which can only drop a value in place, so it cannot be moved out without
+    /// leaking the allocation. Outstanding owned handles (e.g. returned by
+    /// [`Idr::get_owned()`]) remain valid. A value is dropped once the last
+    /// handle to it is dropped.
+    ///
+    /// If the returned iterator is dropped before being fully consumed,
+    /// remaining entries are removed anyway.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use idr_ebr::Idr;
+    ///
+    /// let mut idr = Idr::default();
+    /// let foo_key = idr.insert("foo").unwrap();
+    /// let bar_key = idr.insert("bar").unwrap();
+    ///
+    /// let drained = idr.drain().collect::<Vec<_>>();
+    /// assert_eq!(drained.len(), 2);
+    /// assert_eq!(drained[0].0, foo_key);
+    /// assert_eq!(drained[0].1, "foo");
+    /// assert_eq!(drained[1].0, bar_key);
+    /// assert_eq!(drained[1].1, "bar");
+    /// assert!(idr.is_empty());
+    /// ```
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, T, C, K> {
+        Drain::new(&self.pages, &self.feed)
+    }
+}
+
+impl<T: 'static, C: Config, A: PageAllocator, K: IdrKey> IntoIterator for Idr<T, C, A, K> {
+    type IntoIter = IntoIter<T, C, A, K>;
+    type Item = (K, OwnedEntry<T>);
+
+    /// Returns an iterator over entries in the IDR, ordered by keys.
+    ///
+    /// Values are yielded as [`OwnedEntry`] instead of `T`, see
+    /// [`Idr::drain()`] for details.
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<'s, T: 'static, C: Config, A: PageAllocator, K: IdrKey> IntoIterator
+    for &'s mut Idr<T, C, A, K>
+{
+    type IntoIter = IterMut<'s, T, C, K>;
+    type Item = (K, &'s mut T);
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, C, A: PageAllocator, K: IdrKey> Drop for Idr<T, C, A, K> {
+    fn drop(&mut self) {
+        let alloc = self.page_control.allocator();
+
+        for page in &mut *self.pages {
+            // SAFETY: The IDR is dropped, so slots aren't used anymore,
+            // and all pages are allocated using this allocator.
+            unsafe { page.release(alloc) };
+        }
+    }
 }
 
-impl<T, C: Config> fmt::Debug for Idr<T, C> {
+impl<T, C: Config, A: PageAllocator, K: IdrKey> fmt::Debug for Idr<T, C, A, K> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Idr")
             .field("allocated_pages", &self.page_control.allocated())
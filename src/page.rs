@@ -1,14 +1,18 @@
-use std::{ptr, slice};
+use std::{ops::Range, ptr, ptr::NonNull, slice};
 
 use crate::{
-    config::Config,
+    allocator::PageAllocator,
+    config::{Config, ReusePolicy},
     control::PageControl,
-    key::{Key, PageNo},
+    error::AllocError,
+    feed::{ChangeEvent, ChangeFeed},
+    key::{Generation, Key, PageNo},
     loom::{
         alloc,
-        sync::atomic::{AtomicPtr, AtomicU32, Ordering},
+        sync::atomic::{AtomicBool, AtomicPtr, AtomicU32, AtomicU64, Ordering},
     },
     slot::Slot,
+    stats::{self, PageStats},
     BorrowedEntry, EbrGuard,
 };
 
@@ -18,7 +22,16 @@ pub(crate) struct Page<T, C> {
     start_slot_id: u32,
     capacity: u32,
     slots: AtomicPtr<Slot<T, C>>,
-    free_head: AtomicU32, // MAX means no free slots
+    free_list: FreeList,
+    // Freed slots, which aren't in the free list yet, see `ReusePolicy`.
+    pending: AtomicU32, // MAX means no pending slots
+    // Pending slots waiting for a grace period, see `ReusePolicy::Quarantine`.
+    quarantine: sdd::AtomicShared<Batch>, // null means no quarantined slots
+    used: AtomicU32,                      // reserved or occupied slots
+    retired: AtomicU32,                   // see `Config::RETIRE_SLOTS_ON_WRAP`
+    // Generations of slots of the deallocated page, see `Page::deallocate()`.
+    // Accessed only under the page control's lock or exclusively.
+    generations: AtomicPtr<u32>,
 }
 
 impl<T: 'static, C: Config> Page<T, C> {
@@ -27,7 +40,12 @@ impl<T: 'static, C: Config> Page<T, C> {
             start_slot_id: page_no.start_slot_id(),
             capacity: page_no.capacity(),
             slots: AtomicPtr::new(ptr::null_mut()),
-            free_head: AtomicU32::new(0),
+            free_list: FreeList::new(0),
+            pending: AtomicU32::new(u32::MAX),
+            quarantine: sdd::AtomicShared::null(),
+            used: AtomicU32::new(0),
+            retired: AtomicU32::new(0),
+            generations: AtomicPtr::new(ptr::null_mut()),
         }
     }
 
@@ -38,93 +56,522 @@ impl<T: 'static, C: Config> Page<T, C> {
         let slots_ptr = self.slots.load(Ordering::Acquire);
         debug_assert!(!slots_ptr.is_null());
 
-        let mut free_head = self.free_head.load(Ordering::Acquire);
-        loop {
-            slot.set_next_free(free_head);
+        // Retired slots are never reused, so they aren't linked into the free list.
+        if C::RETIRE_SLOTS_ON_WRAP && slot.is_retired() {
+            // It's only for statistics, so no need to synchronize with anything.
+            // Readers can still observe the slot counted twice or not at all,
+            // because counters are updated separately.
+            self.used.fetch_sub(1, Ordering::Relaxed);
+            self.retired.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
 
-            // SAFETY: Derived from the invariant that the slot belongs to this page.
-            let slot_index = ptr::from_ref(slot).offset_from(slots_ptr);
-            debug_assert!((0isize..(1 << 31)).contains(&slot_index));
+        // SAFETY: Derived from the invariant that the slot belongs to this page.
+        let slot_index = ptr::from_ref(slot).offset_from(slots_ptr);
+        debug_assert!((0isize..(1 << 31)).contains(&slot_index));
 
-            // It never truncates, because the index is less than 2^31.
-            // This is because the slot id includes a bit of a page.
-            #[allow(clippy::cast_sign_loss)]
-            let slot_index = slot_index as u32;
-            debug_assert!(slot_index < self.capacity);
+        // It never truncates, because the index is less than 2^31.
+        // This is because the slot id includes a bit of a page.
+        #[allow(clippy::cast_sign_loss)]
+        let slot_index = slot_index as u32;
+        debug_assert!(slot_index < self.capacity);
 
-            if let Err(new_free_head) = self.free_head.compare_exchange(
-                free_head,
-                slot_index,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                free_head = new_free_head;
-            } else {
-                break;
+        // Other policies keep freed slots aside, see `Page::refill()`.
+        match C::REUSE_POLICY {
+            ReusePolicy::Lifo => self.free_list.push_chain(slot_index, slot),
+            ReusePolicy::Fifo | ReusePolicy::Quarantine => {
+                push_chain(&self.pending, slot_index, slot);
             }
         }
+
+        // It's only for statistics, so no need to synchronize with anything.
+        // It never underflows, because the slot has been reserved before.
+        self.used.fetch_sub(1, Ordering::Relaxed);
     }
 
-    pub(crate) fn reserve(&self, page_control: &PageControl) -> Option<(Key, &Slot<T, C>)> {
-        let slots_ptr =
-            page_control.get_or_lock(|| self.slots.load(Ordering::Acquire), || self.allocate());
+    /// Moves pending slots to the free list according to the reuse policy.
+    /// Returns `false` if there are no slots to move.
+    ///
+    /// Taken slots aren't reachable by other threads until they're moved.
+    /// Other threads don't wait for it to stay lock-free, so they can consider
+    /// the page full for a moment, see `ReusePolicy`.
+    fn refill(&self, slots_ptr: *const Slot<T, C>) -> bool {
+        match C::REUSE_POLICY {
+            ReusePolicy::Lifo => false,
+            ReusePolicy::Fifo => {
+                let head = self.pending.swap(u32::MAX, Ordering::AcqRel);
+                if head == u32::MAX {
+                    return false;
+                }
 
-        let mut free_head = self.free_head.load(Ordering::Acquire);
-        let (slot_index, slot) = loop {
-            if free_head == u32::MAX {
-                return None;
+                // Pending slots are pushed in order of freeing, so the chain
+                // is reversed to take the least recently freed slot first.
+                // SAFETY: The chain has been taken, so it's owned exclusively.
+                let (head, tail) = unsafe { reverse_chain(slots_ptr, head) };
+                self.free_list.push_chain(head, tail);
+                true
             }
+            ReusePolicy::Quarantine => {
+                let guard = EbrGuard::new();
+                let mut refilled = false;
 
-            debug_assert!(free_head < self.capacity);
+                // Only one batch is quarantined at a time, so pending
+                // slots wait for it to be released.
+                let batch = self.quarantine.load(Ordering::Acquire, &guard.0);
+                if let Some(quarantined) = batch.as_ref() {
+                    if !quarantined.released.load(Ordering::Acquire) {
+                        return false;
+                    }
 
-            // SAFETY: Both the starting and resulting pointer is in bounds of the same
-            // allocated object, because `free_head` is always less than `self.capacity`.
-            let slot = unsafe { &*slots_ptr.add(free_head as usize) };
+                    // Only one thread takes the released batch.
+                    if self
+                        .quarantine
+                        .compare_exchange(
+                            batch,
+                            (None, sdd::Tag::None),
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                            &guard.0,
+                        )
+                        .is_err()
+                    {
+                        return false;
+                    }
 
-            let next_free_head = slot.next_free();
-            debug_assert!(next_free_head == u32::MAX || next_free_head < self.capacity);
+                    let head = quarantined.head.load(Ordering::Relaxed);
+                    // SAFETY: The chain has been taken, so it's owned exclusively.
+                    let tail = unsafe { chain_tail(slots_ptr, head) };
+                    self.free_list.push_chain(head, tail);
+                    refilled = true;
+                }
 
-            if let Err(new_free_head) = self.free_head.compare_exchange(
-                free_head,
-                next_free_head,
-                Ordering::AcqRel,
-                Ordering::Acquire,
-            ) {
-                free_head = new_free_head;
-            } else {
-                break (free_head, slot);
+                let head = self.pending.swap(u32::MAX, Ordering::AcqRel);
+                if head == u32::MAX {
+                    return refilled;
+                }
+
+                let batch = sdd::Shared::new(Batch {
+                    head: AtomicU32::new(head),
+                    released: AtomicBool::new(false),
+                });
+
+                if self
+                    .quarantine
+                    .compare_exchange(
+                        sdd::Ptr::null(),
+                        (Some(batch.clone()), sdd::Tag::None),
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                        &guard.0,
+                    )
+                    .is_err()
+                {
+                    // Another batch has been quarantined concurrently,
+                    // so return slots back to wait for it.
+                    // SAFETY: The chain has been taken, so it's owned exclusively.
+                    let tail = unsafe { chain_tail(slots_ptr, head) };
+                    push_chain(&self.pending, head, tail);
+                    return refilled;
+                }
+
+                // All taken slots have been freed before, so the closure runs
+                // once guards, which could observe them occupied, are dropped.
+                let release = move || batch.released.store(true, Ordering::Release);
+                guard.0.defer_execute(release);
+                // Make the grace period pass sooner.
+                guard.0.accelerate();
+
+                refilled
+            }
+        }
+    }
+
+    /// Reserves a free slot, allocating the page if needed.
+    /// Returns `Ok(None)` if there are no free slots.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn reserve(
+        &self,
+        page_control: &PageControl<impl PageAllocator>,
+    ) -> Result<Option<(Key, &Slot<T, C>)>, AllocError> {
+        Ok(self
+            .reserve_many(page_control, 1)?
+            .and_then(|mut reserved| {
+                let pair = reserved.next();
+                debug_assert!(pair.is_some() && reserved.remaining == 0);
+                pair
+            }))
+    }
+
+    /// Reserves up to `max` free slots at once, allocating the page if needed.
+    /// Returns `Ok(None)` if there are no free slots.
+    ///
+    /// Slots that aren't taken from the returned iterator are freed on drop.
+    pub(crate) fn reserve_many(
+        &self,
+        page_control: &PageControl<impl PageAllocator>,
+        max: u32,
+    ) -> Result<Option<Reserved<'_, T, C>>, AllocError> {
+        debug_assert!(max > 0);
+
+        let slots_ptr = self.get_or_allocate(page_control)?;
+
+        // Stale links can form a cycle, so the walk is limited by the capacity.
+        let max = max.min(self.capacity);
+
+        let (free_head, count) = loop {
+            // SAFETY: The page is allocated and free indices are always less
+            // than `self.capacity`.
+            if let Some(popped) = unsafe { self.free_list.pop_chain(slots_ptr, max) } {
+                break popped;
+            }
+
+            if !self.refill(slots_ptr) {
+                return Ok(None);
             }
         };
 
-        // SAFETY: `slot_id` is always non-zero, because it includes a bit of a page.
-        let key = unsafe { Key::new_unchecked(self.start_slot_id + slot_index, slot.generation()) };
+        debug_assert!(free_head < self.capacity);
 
-        Some((key, slot))
+        // It's only for statistics, so no need to synchronize with anything.
+        self.used.fetch_add(count, Ordering::Relaxed);
+
+        Ok(Some(Reserved {
+            page: self,
+            slots_ptr,
+            next: free_head,
+            remaining: count,
+        }))
+    }
+
+    pub(crate) fn remove(&self, key: Key, guard: &EbrGuard, feed: &ChangeFeed) -> bool {
+        self.take(key, guard, feed)
+            .map(sdd::Shared::release)
+            .is_some()
+    }
+
+    pub(crate) fn take(
+        &self,
+        key: Key,
+        guard: &EbrGuard,
+        feed: &ChangeFeed,
+    ) -> Option<sdd::Shared<T>> {
+        let slot = self.slot(key)?;
+        let data = slot.uninit(key, guard)?;
+
+        // Record before freeing the slot, see `ChangeFeed::record()`.
+        feed.record(ChangeEvent::Removed(key));
+
+        // SAFETY: The slot belongs to this page.
+        unsafe { self.add_free(slot) };
+        Some(data)
+    }
+
+    /// Removes entries for which the predicate returns `false`.
+    pub(crate) fn retain(
+        &self,
+        f: &mut impl FnMut(Key, &T) -> bool,
+        guard: &EbrGuard,
+        feed: &ChangeFeed,
+    ) {
+        let Some(iter) = self.iter(guard) else {
+            return;
+        };
+
+        for (key, entry) in iter {
+            if f(key, &entry) {
+                continue;
+            }
+
+            // SAFETY: The page is allocated, because the entry is yielded.
+            let slot = unsafe { self.slot(key).unwrap_unchecked() };
+
+            // Skip the entry if it has been removed or replaced concurrently.
+            if let Ok(data) = slot.uninit_if(key, entry.as_ptr(), guard) {
+                feed.record(ChangeEvent::Removed(key));
+
+                // SAFETY: The slot belongs to this page.
+                unsafe { self.add_free(slot) };
+                let _ = data.release();
+            }
+        }
+    }
+
+    pub(crate) fn replace(&self, key: Key, value: T) -> Option<sdd::Shared<T>> {
+        self.slot(key)?.replace(key, value)
+    }
+
+    pub(crate) fn compare_and_swap(
+        &self,
+        key: Key,
+        current: sdd::Ptr<'_, T>,
+        value: T,
+    ) -> Option<sdd::Shared<T>> {
+        self.slot(key)?.compare_and_swap(key, current, value)
+    }
+
+    pub(crate) fn update(&self, key: Key, f: impl FnMut(&T) -> T) -> Option<sdd::Shared<T>> {
+        self.slot(key)?.update(key, f)
     }
 
-    pub(crate) fn remove(&self, key: Key) -> bool {
+    pub(crate) fn get<'g>(&self, key: Key, guard: &'g EbrGuard) -> Option<BorrowedEntry<'g, T>> {
+        let slot = self.slot(key)?;
+        BorrowedEntry::new(slot.get(key, guard))
+    }
+
+    pub(crate) fn get_mut(&mut self, key: Key) -> Option<&mut T> {
         let slots_ptr = self.slots.load(Ordering::Acquire);
         if slots_ptr.is_null() {
-            return false;
+            return None;
         }
 
-        let slot_id = key.slot_id::<C>();
-        let slot_index = slot_id - self.start_slot_id;
+        let slot_index = key.slot_id::<C>() - self.start_slot_id;
         debug_assert!(slot_index < self.capacity);
 
         // SAFETY: Both the starting and resulting pointer is in bounds of the same
-        // allocated object, because `slot_id` belongs to this page.
-        let slot = unsafe { &*slots_ptr.add(slot_index as usize) };
-        if !slot.uninit(key) {
+        // allocated object, because `slot_index` belongs to this page.
+        // The slot is borrowed mutably, because the page is borrowed mutably.
+        let slot = unsafe { &mut *slots_ptr.add(slot_index as usize) };
+        slot.get_mut(key)
+    }
+
+    /// Returns the first occupied slot starting from `*slot_index`
+    /// and advances `*slot_index` past it.
+    pub(crate) fn get_next<'g>(
+        &self,
+        slot_index: &mut u32,
+        guard: &'g EbrGuard,
+    ) -> Option<(Key, BorrowedEntry<'g, T>)> {
+        debug_assert!(*slot_index <= self.capacity);
+
+        let (key, entry) = self.iter_range(*slot_index..self.capacity, guard)?.next()?;
+        *slot_index = key.slot_id::<C>() - self.start_slot_id + 1;
+        Some((key, entry))
+    }
+
+    /// Removes the first occupied slot starting from `*slot_index`
+    /// and advances `*slot_index` past it.
+    pub(crate) fn take_next(
+        &self,
+        slot_index: &mut u32,
+        guard: &EbrGuard,
+        feed: &ChangeFeed,
+    ) -> Option<(Key, sdd::Shared<T>)> {
+        let slots_ptr = self.slots.load(Ordering::Acquire);
+        if slots_ptr.is_null() {
+            return None;
+        }
+
+        while *slot_index < self.capacity {
+            // SAFETY: Both the starting and resulting pointer is in bounds of the same
+            // allocated object, because `slot_index` is less than `self.capacity`.
+            let slot = unsafe { &*slots_ptr.add(*slot_index as usize) };
+
+            // SAFETY: `slot_id` is always non-zero, because it includes a bit of a page.
+            let key =
+                unsafe { Key::new_unchecked(self.start_slot_id + *slot_index, slot.generation()) };
+
+            *slot_index += 1;
+
+            if let Some(data) = slot.uninit(key, guard) {
+                feed.record(ChangeEvent::Removed(key));
+
+                // SAFETY: The slot belongs to this page.
+                unsafe { self.add_free(slot) };
+                return Some((key, data));
+            }
+        }
+
+        None
+    }
+
+    /// Places the value into the slot corresponding to the key, unlinking
+    /// the slot from the free list, or from pending or quarantined slots.
+    /// The generation is taken from the key.
+    /// Returns the value back if the slot is reserved, occupied or retired.
+    ///
+    /// The page must be allocated.
+    pub(crate) fn insert_at(&mut self, key: Key, value: T) -> Result<(), T> {
+        let slots_ptr = self.slots.load(Ordering::Acquire);
+        debug_assert!(!slots_ptr.is_null());
+
+        let slot_index = key.slot_id::<C>() - self.start_slot_id;
+        debug_assert!(slot_index < self.capacity);
+
+        // The page is borrowed mutably, so lists cannot be changed concurrently.
+        let free_head = self.free_list.head();
+        let pending = self.pending.load(Ordering::Acquire);
+        let guard = EbrGuard::new();
+        let batch = self.quarantine.load(Ordering::Acquire, &guard.0).as_ref();
+        let quarantined = batch.map_or(u32::MAX, |batch| batch.head.load(Ordering::Relaxed));
+
+        // SAFETY: Chains cannot be changed, because the page is borrowed mutably.
+        let unlink = |head| unsafe { unlink_from_chain(slots_ptr, head, slot_index) };
+
+        if let Some(head) = unlink(free_head) {
+            self.free_list.set_head(head);
+        } else if let Some(head) = unlink(pending) {
+            self.pending.store(head, Ordering::Release);
+        } else if let Some(head) = unlink(quarantined) {
+            if head == u32::MAX {
+                self.quarantine
+                    .swap((None, sdd::Tag::None), Ordering::Release);
+            } else if let Some(batch) = batch {
+                batch.head.store(head, Ordering::Relaxed);
+            }
+        } else {
+            // The slot isn't free.
+            return Err(value);
+        }
+
+        // SAFETY: Both the starting and resulting pointer is in bounds of the same
+        // allocated object, because `slot_index` belongs to this page.
+        // The slot is borrowed mutably, because the page is borrowed mutably.
+        let slot = unsafe { &mut *slots_ptr.add(slot_index as usize) };
+
+        let restored = slot.restore(key, Some(value));
+        debug_assert!(restored);
+
+        // It's only for statistics, so no need to synchronize with anything.
+        self.used.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Restores the slot corresponding to the key: the generation is taken
+    /// from the key, and the value is placed if provided.
+    /// Returns `false` if the slot is occupied.
+    ///
+    /// The page must be allocated. Free slots aren't unlinked, so
+    /// `rebuild_free_list()` must be called after all slots are restored.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore(&mut self, key: Key, value: Option<T>) -> bool {
+        let slots_ptr = self.slots.load(Ordering::Acquire);
+        debug_assert!(!slots_ptr.is_null());
+
+        let slot_index = key.slot_id::<C>() - self.start_slot_id;
+        debug_assert!(slot_index < self.capacity);
+
+        // SAFETY: Both the starting and resulting pointer is in bounds of the same
+        // allocated object, because `slot_index` belongs to this page.
+        // The slot is borrowed mutably, because the page is borrowed mutably.
+        let slot = unsafe { &mut *slots_ptr.add(slot_index as usize) };
+        slot.restore(key, value)
+    }
+
+    /// Links all vacant slots, except retired ones, into the free list in order
+    /// of indexes and recounts occupied and retired ones. Does nothing if the
+    /// page isn't allocated.
+    #[cfg(feature = "serde")]
+    pub(crate) fn rebuild_free_list(&mut self) {
+        let slots_ptr = self.slots.load(Ordering::Acquire);
+        if slots_ptr.is_null() {
+            return;
+        }
+
+        // SAFETY: Slots are properly initialized and borrowed mutably,
+        // because the page is borrowed mutably.
+        let slots = unsafe { slice::from_raw_parts_mut(slots_ptr, self.capacity as usize) };
+
+        let mut free_head = u32::MAX;
+        let mut used = 0;
+        let mut retired = 0;
+
+        for (slot_index, slot) in slots.iter_mut().enumerate().rev() {
+            if slot.is_retired() {
+                retired += 1;
+            } else if slot.is_vacant() {
+                slot.set_next_free(free_head);
+                // It never truncates, because the index is less than `self.capacity`.
+                free_head = slot_index as u32;
+            } else {
+                used += 1;
+            }
+        }
+
+        self.free_list.set_head(free_head);
+        self.pending.store(u32::MAX, Ordering::Relaxed);
+        self.quarantine
+            .swap((None, sdd::Tag::None), Ordering::Relaxed);
+        self.used.store(used, Ordering::Relaxed);
+        self.retired.store(retired, Ordering::Relaxed);
+    }
+
+    /// Marks the vacant slot corresponding to the key as retired.
+    /// Returns `false` if the slot is occupied.
+    ///
+    /// The page must be allocated, and `rebuild_free_list()` must be called
+    /// after all slots are restored.
+    #[cfg(feature = "serde")]
+    pub(crate) fn restore_retired(&mut self, key: Key) -> bool {
+        let slots_ptr = self.slots.load(Ordering::Acquire);
+        debug_assert!(!slots_ptr.is_null());
+
+        let slot_index = key.slot_id::<C>() - self.start_slot_id;
+        debug_assert!(slot_index < self.capacity);
+
+        // SAFETY: Both the starting and resulting pointer is in bounds of the same
+        // allocated object, because `slot_index` belongs to this page.
+        // The slot is borrowed mutably, because the page is borrowed mutably.
+        let slot = unsafe { &mut *slots_ptr.add(slot_index as usize) };
+        if !slot.restore(key, None) {
             return false;
         }
 
-        // SAFETY: The slot belongs to this page.
-        unsafe { self.add_free(slot) };
+        slot.retire();
         true
     }
 
-    pub(crate) fn get<'g>(&self, key: Key, guard: &'g EbrGuard) -> Option<BorrowedEntry<'g, T>> {
+    /// Returns the state of every slot with its key, skipping vacant slots
+    /// which have never been occupied. Returns an empty iterator if the page
+    /// isn't allocated.
+    ///
+    /// Every slot is visited once, so it's reported in a single state even if
+    /// it's changed concurrently.
+    #[cfg(feature = "serde")]
+    pub(crate) fn slot_states<'g>(
+        &'g self,
+        guard: &'g EbrGuard,
+    ) -> impl Iterator<Item = (Key, SlotState<'g, T>)> + 'g {
+        let slots_ptr = self.slots.load(Ordering::Acquire);
+        let slots = if slots_ptr.is_null() {
+            &[]
+        } else {
+            // SAFETY: Slots are properly initialized.
+            unsafe { slice::from_raw_parts(slots_ptr, self.capacity as usize) }
+        };
+
+        (self.start_slot_id..)
+            .zip(slots)
+            .filter_map(|(slot_id, slot)| {
+                let generation = slot.generation();
+                // SAFETY: `slot_id` is always non-zero, because it includes a bit of a page.
+                let key = unsafe { Key::new_unchecked(slot_id, generation) };
+
+                let state = if let Some(entry) = BorrowedEntry::new(slot.get(key, guard)) {
+                    SlotState::Occupied(entry)
+                } else if slot.is_retired() {
+                    SlotState::Retired
+                } else if generation.to_u32() != 0 {
+                    SlotState::Vacant
+                } else {
+                    return None;
+                };
+
+                Some((key, state))
+            })
+    }
+
+    /// Prefetches the slot corresponding to the key into the cache.
+    #[inline]
+    pub(crate) fn prefetch(&self, key: Key) {
+        if let Some(slot) = self.slot(key) {
+            prefetch(slot);
+        }
+    }
+
+    /// Returns the slot corresponding to the key, or `None` if the page isn't
+    /// allocated. The key must belong to this page.
+    #[inline]
+    fn slot(&self, key: Key) -> Option<&Slot<T, C>> {
         // TODO: `crossbeam_utils::AtomicConsume`?
         let slots_ptr = self.slots.load(Ordering::Acquire);
         if slots_ptr.is_null() {
@@ -136,13 +583,102 @@ impl<T: 'static, C: Config> Page<T, C> {
 
         // SAFETY: Both the starting and resulting pointer is in bounds of the same
         // allocated object, because `slot_index` belongs to this page.
-        let slot = unsafe { &*slots_ptr.add(slot_index as usize) };
-        BorrowedEntry::new(slot.get(key, guard))
+        Some(unsafe { &*slots_ptr.add(slot_index as usize) })
+    }
+
+    /// Allocates the page if it isn't allocated yet.
+    /// Returns the pointer to the first slot.
+    pub(crate) fn get_or_allocate(
+        &self,
+        page_control: &PageControl<impl PageAllocator>,
+    ) -> Result<*const Slot<T, C>, AllocError> {
+        page_control.get_or_lock(
+            || self.slots.load(Ordering::Acquire),
+            || self.allocate(page_control.allocator()),
+        )
+    }
+
+    /// Returns the number of slots in the page.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub(crate) fn is_allocated(&self) -> bool {
+        !self.slots.load(Ordering::Relaxed).is_null()
+    }
+
+    /// Returns the number of free slots, or zero if the page isn't allocated.
+    pub(crate) fn free(&self) -> u32 {
+        if !self.is_allocated() {
+            return 0;
+        }
+
+        // Counters are loaded separately, so a slot being retired concurrently
+        // can be counted twice.
+        self.capacity
+            .saturating_sub(self.used())
+            .saturating_sub(self.retired())
+    }
+
+    /// Returns the number of reserved or occupied slots.
+    pub(crate) fn used(&self) -> u32 {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of retired slots, see `Config::RETIRE_SLOTS_ON_WRAP`.
+    pub(crate) fn retired(&self) -> u32 {
+        self.retired.load(Ordering::Relaxed)
+    }
+
+    /// Returns statistics of the page, or `None` if the page isn't allocated.
+    pub(crate) fn stats(&self, page_no: usize) -> Option<PageStats> {
+        if self.slots.load(Ordering::Acquire).is_null() {
+            return None;
+        }
+
+        let capacity = self.capacity as usize;
+        let occupied = self.used() as usize;
+        let retired = self.retired() as usize;
+
+        Some(PageStats {
+            page_no,
+            capacity,
+            occupied,
+            retired,
+            // See `Page::free()` for details.
+            free: capacity.saturating_sub(occupied).saturating_sub(retired),
+            slots_bytes: self.layout().size(),
+            containers_bytes: occupied * stats::container_size::<T>(),
+        })
     }
 
     /// Iterates over occupied slots, or `None` if the page isn't allocated.
     #[allow(clippy::iter_not_returning_iterator)]
     pub(crate) fn iter<'g>(&self, guard: &'g EbrGuard) -> Option<Iter<'g, '_, T, C>> {
+        self.iter_range(0..self.capacity, guard)
+    }
+
+    /// Iterates over occupied slots starting from the slot corresponding to
+    /// the key (inclusive), or `None` if the page isn't allocated.
+    /// The key must belong to this page, but its generation is ignored.
+    pub(crate) fn iter_from<'g>(
+        &self,
+        key: Key,
+        guard: &'g EbrGuard,
+    ) -> Option<Iter<'g, '_, T, C>> {
+        let slot_index = key.slot_id::<C>() - self.start_slot_id;
+        debug_assert!(slot_index < self.capacity);
+        self.iter_range(slot_index..self.capacity, guard)
+    }
+
+    /// Iterates over occupied slots in the range of slot indexes,
+    /// or `None` if the page isn't allocated.
+    pub(crate) fn iter_range<'g>(
+        &self,
+        range: Range<u32>,
+        guard: &'g EbrGuard,
+    ) -> Option<Iter<'g, '_, T, C>> {
         let slots_ptr = self.slots.load(Ordering::Acquire);
         if slots_ptr.is_null() {
             return None;
@@ -152,29 +688,49 @@ impl<T: 'static, C: Config> Page<T, C> {
         let slots = unsafe { slice::from_raw_parts(slots_ptr, self.capacity as usize) };
 
         Some(Iter {
-            slots,
+            first_slot_id: self.start_slot_id + range.start,
+            slots: &slots[range.start as usize..range.end as usize],
+            guard,
+        })
+    }
+
+    /// Iterates mutably over occupied slots, which aren't shared by owned
+    /// handles, or `None` if the page isn't allocated.
+    #[allow(clippy::iter_not_returning_iterator)]
+    pub(crate) fn iter_mut(&mut self) -> Option<IterMut<'_, T, C>> {
+        let slots_ptr = self.slots.load(Ordering::Acquire);
+        if slots_ptr.is_null() {
+            return None;
+        }
+
+        // SAFETY: Slots are properly initialized and borrowed mutably,
+        // because the page is borrowed mutably.
+        let slots = unsafe { slice::from_raw_parts_mut(slots_ptr, self.capacity as usize) };
+
+        Some(IterMut {
+            slots: slots.iter_mut(),
             // It never underflows, because slot ids are non-zero.
             prev_slot_id: self.start_slot_id - 1,
-            guard,
         })
     }
 
     #[cold]
     #[inline(never)]
-    fn allocate(&self) {
+    fn allocate(&self, alloc: &impl PageAllocator) -> Result<(), AllocError> {
         debug_assert!(self.slots.load(Ordering::Relaxed).is_null());
 
-        let layout =
-            alloc::Layout::array::<Slot<T, C>>(self.capacity as usize).expect("invalid layout");
+        let layout = self.layout();
         assert_ne!(layout.size(), 0);
 
-        // SAFETY: `layout` is valid and non-zero because of assertions above.
-        let slots_ptr = unsafe { alloc::alloc(layout) };
-
-        assert!(!slots_ptr.is_null(), "failed to allocate memory");
+        let slots_ptr = alloc.allocate(layout).ok_or(AllocError)?;
 
         #[allow(clippy::cast_ptr_alignment)] // ensured by `layout` above
-        let slots_ptr = slots_ptr.cast::<Slot<T, C>>();
+        let slots_ptr = slots_ptr.as_ptr().cast::<Slot<T, C>>();
+
+        // Restore generations if the page has been deallocated before.
+        // They're released only after the page is allocated, so they aren't lost
+        // if something panics in the meantime and allocation is retried.
+        let generations_ptr = self.generations.load(Ordering::Relaxed);
 
         for slot_index in 0..self.capacity {
             // SAFETY: Both the starting and resulting pointer is in bounds of the same
@@ -189,7 +745,14 @@ impl<T: 'static, C: Config> Page<T, C> {
                 u32::MAX
             };
 
-            let slot = Slot::new(next_free);
+            let generation = if generations_ptr.is_null() {
+                0
+            } else {
+                // SAFETY: Saved generations have the same length as the page.
+                unsafe { *generations_ptr.add(slot_index as usize) }
+            };
+
+            let slot = Slot::new(next_free, Generation::new(generation));
 
             // SAFETY: The slot is properly aligned.
             unsafe { slot_ptr.write(slot) };
@@ -197,17 +760,74 @@ impl<T: 'static, C: Config> Page<T, C> {
 
         debug_assert!(self.slots.load(Ordering::Relaxed).is_null());
         self.slots.store(slots_ptr, Ordering::Release);
+
+        drop(self.take_generations());
+        Ok(())
     }
-}
 
-impl<T, C> Drop for Page<T, C> {
-    fn drop(&mut self) {
+    /// Deallocates the page if it's allocated and has no reserved, occupied
+    /// or retired slots. Returns `true` if the page has been deallocated.
+    ///
+    /// Generations of slots are preserved and restored by the next allocation,
+    /// so keys issued before the deallocation cannot resolve into new entries.
+    pub(crate) fn deallocate(&mut self, alloc: &impl PageAllocator) -> bool {
         let slots_ptr = self.slots.load(Ordering::Acquire);
+        if slots_ptr.is_null() || self.used() != 0 || self.retired() != 0 {
+            return false;
+        }
 
-        if slots_ptr.is_null() {
-            return;
+        // SAFETY: Slots are properly initialized.
+        let slots = unsafe { slice::from_raw_parts(slots_ptr, self.capacity as usize) };
+
+        // Fresh slots start with zero generation, so save only non-zero ones.
+        if slots.iter().any(|slot| slot.generation().to_u32() != 0) {
+            let generations = slots
+                .iter()
+                .map(|slot| slot.generation().to_u32())
+                .collect::<Box<[u32]>>();
+
+            let generations_ptr = Box::into_raw(generations).cast::<u32>();
+            let prev = self.generations.swap(generations_ptr, Ordering::Relaxed);
+            debug_assert!(prev.is_null());
         }
 
+        // SAFETY: The page is accessed exclusively and slots aren't used anymore.
+        unsafe { self.free_slots(slots_ptr, alloc) };
+
+        self.slots.store(ptr::null_mut(), Ordering::Relaxed);
+        self.free_list.set_head(0);
+        self.pending.store(u32::MAX, Ordering::Relaxed);
+        self.quarantine
+            .swap((None, sdd::Tag::None), Ordering::Relaxed);
+        true
+    }
+}
+
+impl<T, C> Page<T, C> {
+    fn layout(&self) -> alloc::Layout {
+        alloc::Layout::array::<Slot<T, C>>(self.capacity as usize).expect("invalid layout")
+    }
+
+    fn take_generations(&self) -> Option<Box<[u32]>> {
+        let generations_ptr = self.generations.swap(ptr::null_mut(), Ordering::Relaxed);
+        if generations_ptr.is_null() {
+            return None;
+        }
+
+        let generations = ptr::slice_from_raw_parts_mut(generations_ptr, self.capacity as usize);
+
+        // SAFETY: The pointer is created by `Box::into_raw()` in `deallocate()`
+        // with the same length.
+        Some(unsafe { Box::from_raw(generations) })
+    }
+
+    /// Drops slots and deallocates memory.
+    ///
+    /// # Safety
+    ///
+    /// The pointer must be allocated by `allocate()` using the same allocator
+    /// and not used anymore.
+    unsafe fn free_slots(&self, slots_ptr: *mut Slot<T, C>, alloc: &impl PageAllocator) {
         // Call destructors.
         for slot_index in 0..self.capacity {
             // SAFETY: Both the starting and resulting pointer is in bounds of the same
@@ -222,41 +842,427 @@ impl<T, C> Drop for Page<T, C> {
         }
 
         // Deallocate memory.
-        let layout =
-            alloc::Layout::array::<Slot<T, C>>(self.capacity as usize).expect("invalid layout");
+        let layout = self.layout();
+
+        // SAFETY: The pointer is non-null, because it's allocated by `allocate()`.
+        let slots_ptr = unsafe { NonNull::new_unchecked(slots_ptr.cast::<u8>()) };
 
         // SAFETY:
         // * a block of memory currently allocated via this allocator
         // * layout is the same layout that was used to allocate that block of memory
-        unsafe { alloc::dealloc(slots_ptr.cast::<u8>(), layout) };
+        unsafe { alloc.deallocate(slots_ptr, layout) };
+    }
+
+    /// Releases all resources of the page.
+    ///
+    /// It isn't implemented as `Drop`, because the page doesn't own the allocator.
+    /// Thus, it must be called by the owner of pages, otherwise memory is leaked.
+    ///
+    /// # Safety
+    ///
+    /// Slots must be allocated using the provided allocator and not used anymore.
+    pub(crate) unsafe fn release(&mut self, alloc: &impl PageAllocator) {
+        drop(self.take_generations());
+
+        let slots_ptr = self.slots.swap(ptr::null_mut(), Ordering::Acquire);
+
+        if slots_ptr.is_null() {
+            return;
+        }
+
+        // SAFETY: Guaranteed by the caller.
+        unsafe { self.free_slots(slots_ptr, alloc) };
+    }
+}
+
+#[inline]
+fn prefetch<T>(value: &T) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+
+        // SAFETY: Prefetching is only a hint, and `sse` is always available on x86-64.
+        unsafe { _mm_prefetch::<_MM_HINT_T0>(ptr::from_ref(value).cast()) };
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = value;
+}
+
+// === Quarantine ===
+
+/// A chain of freed slots waiting for a grace period.
+struct Batch {
+    head: AtomicU32,
+    // Set by a closure deferred by `sdd::Guard::defer_execute()`.
+    released: AtomicBool,
+}
+
+// === SlotState ===
+
+/// The state of a slot in a snapshot, see `Page::slot_states()`.
+#[cfg(feature = "serde")]
+pub(crate) enum SlotState<'g, T> {
+    Occupied(BorrowedEntry<'g, T>),
+    // The slot is free, but its generation must be kept.
+    Vacant,
+    // See `Config::RETIRE_SLOTS_ON_WRAP`.
+    Retired,
+}
+
+// === Chains ===
+
+/// A lock-free list of free slots, linked by `Slot::next_free()`.
+///
+/// The head is stored along with a version, which is incremented by every
+/// push and pop. Otherwise, a thread walking the chain before popping it could
+/// take slots that have been reserved and freed again in the meantime (ABA).
+struct FreeList(AtomicU64); // the head in low bits and the version in high bits
+
+impl FreeList {
+    const VERSION: u64 = 1 << 32;
+
+    fn new(head: u32) -> Self {
+        Self(AtomicU64::new(u64::from(head)))
+    }
+
+    /// Returns the head, `u32::MAX` means no free slots.
+    fn head(&self) -> u32 {
+        // It never truncates, the head is stored in low bits.
+        #[allow(clippy::cast_possible_truncation)]
+        let head = self.0.load(Ordering::Acquire) as u32;
+        head
+    }
+
+    /// Replaces the list. Must be called only exclusively.
+    fn set_head(&self, head: u32) {
+        self.0.store(u64::from(head), Ordering::Release);
+    }
+
+    /// Pushes a chain of slots from `head` to `tail`.
+    fn push_chain<T: 'static, C: Config>(&self, head: u32, tail: &Slot<T, C>) {
+        let mut list = self.0.load(Ordering::Acquire);
+        loop {
+            // It never truncates, the head is stored in low bits.
+            #[allow(clippy::cast_possible_truncation)]
+            tail.set_next_free(list as u32);
+
+            let new_list = (list & !u64::from(u32::MAX)).wrapping_add(Self::VERSION);
+
+            if let Err(actual) = self.0.compare_exchange(
+                list,
+                new_list | u64::from(head),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                list = actual;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Pops a chain of up to `max` slots with a single CAS.
+    /// Returns the head of the chain and its length, or `None` if the list is empty.
+    ///
+    /// # Safety
+    ///
+    /// Indices must be in bounds of the allocated slots.
+    unsafe fn pop_chain<T: 'static, C: Config>(
+        &self,
+        slots_ptr: *const Slot<T, C>,
+        max: u32,
+    ) -> Option<(u32, u32)> {
+        debug_assert!(max > 0);
+
+        let mut list = self.0.load(Ordering::Acquire);
+        loop {
+            // It never truncates, the head is stored in low bits.
+            #[allow(clippy::cast_possible_truncation)]
+            let head = list as u32;
+            if head == u32::MAX {
+                return None;
+            }
+
+            // Walk the chain. Links can be stale if the list has been changed
+            // concurrently, but then the version differs and the CAS fails.
+            let mut count = 1;
+            let mut next = head;
+
+            loop {
+                // SAFETY: Guaranteed by the caller.
+                next = unsafe { &*slots_ptr.add(next as usize) }.next_free();
+
+                if count == max || next == u32::MAX {
+                    break;
+                }
+
+                count += 1;
+            }
+
+            let new_list = (list & !u64::from(u32::MAX)).wrapping_add(Self::VERSION);
+
+            if let Err(actual) = self.0.compare_exchange(
+                list,
+                new_list | u64::from(next),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                list = actual;
+            } else {
+                return Some((head, count));
+            }
+        }
+    }
+}
+
+/// Pushes a chain of slots from `head` to `tail` onto the lock-free list.
+fn push_chain<T: 'static, C: Config>(list: &AtomicU32, head: u32, tail: &Slot<T, C>) {
+    let mut list_head = list.load(Ordering::Acquire);
+    loop {
+        tail.set_next_free(list_head);
+
+        if let Err(new_list_head) =
+            list.compare_exchange(list_head, head, Ordering::AcqRel, Ordering::Acquire)
+        {
+            list_head = new_list_head;
+        } else {
+            break;
+        }
+    }
+}
+
+/// Reverses a chain of slots, returning the new head and tail.
+///
+/// # Safety
+///
+/// The chain must be non-empty and owned exclusively, i.e. not reachable
+/// from lists. Indices must be in bounds of the allocated slots.
+unsafe fn reverse_chain<'s, T: 'static, C: Config>(
+    slots_ptr: *const Slot<T, C>,
+    head: u32,
+) -> (u32, &'s Slot<T, C>) {
+    debug_assert_ne!(head, u32::MAX);
+
+    // SAFETY: Guaranteed by the caller.
+    let tail = unsafe { &*slots_ptr.add(head as usize) };
+    let mut prev = u32::MAX;
+    let mut current = head;
+
+    while current != u32::MAX {
+        // SAFETY: Guaranteed by the caller.
+        let slot = unsafe { &*slots_ptr.add(current as usize) };
+        let next = slot.next_free();
+        slot.set_next_free(prev);
+        prev = current;
+        current = next;
+    }
+
+    (prev, tail)
+}
+
+/// Returns the last slot of a chain.
+///
+/// # Safety
+///
+/// The chain must be non-empty and not changed concurrently.
+/// Indices must be in bounds of the allocated slots.
+unsafe fn chain_tail<'s, T: 'static, C: Config>(
+    slots_ptr: *const Slot<T, C>,
+    head: u32,
+) -> &'s Slot<T, C> {
+    debug_assert_ne!(head, u32::MAX);
+
+    // SAFETY: Guaranteed by the caller.
+    let mut slot = unsafe { &*slots_ptr.add(head as usize) };
+    loop {
+        let next = slot.next_free();
+        if next == u32::MAX {
+            return slot;
+        }
+
+        // SAFETY: Guaranteed by the caller.
+        slot = unsafe { &*slots_ptr.add(next as usize) };
+    }
+}
+
+/// Unlinks the slot from a chain. It takes linear time.
+/// Returns the new head of the chain or `None` if the slot isn't in it.
+///
+/// # Safety
+///
+/// The chain must not be changed concurrently.
+/// Indices must be in bounds of the allocated slots.
+unsafe fn unlink_from_chain<T: 'static, C: Config>(
+    slots_ptr: *const Slot<T, C>,
+    head: u32,
+    slot_index: u32,
+) -> Option<u32> {
+    // Find the previous slot in the chain.
+    let mut prev = None;
+    let mut current = head;
+
+    while current != slot_index {
+        if current == u32::MAX {
+            return None;
+        }
+
+        prev = Some(current);
+
+        // SAFETY: Guaranteed by the caller.
+        current = unsafe { &*slots_ptr.add(current as usize) }.next_free();
+    }
+
+    // SAFETY: Guaranteed by the caller.
+    let next_free = unsafe { &*slots_ptr.add(slot_index as usize) }.next_free();
+
+    match prev {
+        Some(prev) => {
+            // SAFETY: Guaranteed by the caller.
+            unsafe { &*slots_ptr.add(prev as usize) }.set_next_free(next_free);
+            Some(head)
+        }
+        None => Some(next_free),
+    }
+}
+
+// === Reserved ===
+
+/// Iterates over a chain of reserved slots.
+/// Slots that aren't taken are freed on drop.
+#[must_use]
+pub(crate) struct Reserved<'s, T: 'static, C: Config> {
+    page: &'s Page<T, C>,
+    slots_ptr: *const Slot<T, C>,
+    next: u32,
+    remaining: u32,
+}
+
+impl<'s, T: 'static, C: Config> Iterator for Reserved<'s, T, C> {
+    type Item = (Key, &'s Slot<T, C>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        debug_assert!(self.next < self.page.capacity);
+
+        // SAFETY: Both the starting and resulting pointer is in bounds of the same
+        // allocated object, because reserved indices are less than the capacity.
+        // The page cannot be deallocated while it's borrowed.
+        let slot = unsafe { &*self.slots_ptr.add(self.next as usize) };
+        let slot_index = self.next;
+
+        // Links of reserved slots aren't changed by other threads.
+        self.next = slot.next_free();
+        self.remaining -= 1;
+
+        // SAFETY: `slot_id` is always non-zero, because it includes a bit of a page.
+        let key =
+            unsafe { Key::new_unchecked(self.page.start_slot_id + slot_index, slot.generation()) };
+
+        Some((key, slot))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.remaining as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: 'static, C: Config> Drop for Reserved<'_, T, C> {
+    fn drop(&mut self) {
+        let page = self.page;
+
+        for (_, slot) in self {
+            // SAFETY: The slot belongs to this page.
+            unsafe { page.add_free(slot) };
+        }
     }
 }
 
 // === Iter ===
 
-/// Iterates over occupied slots.
+/// Iterates over occupied slots in both directions.
 #[must_use]
 pub(crate) struct Iter<'g, 's, T, C> {
     slots: &'s [Slot<T, C>],
-    prev_slot_id: u32,
+    first_slot_id: u32, // the id of `slots[0]`
     guard: &'g EbrGuard,
 }
 
+impl<'g, T: 'static, C: Config> Iter<'g, '_, T, C> {
+    fn entry(&self, slot_id: u32, slot: &Slot<T, C>) -> Option<(Key, BorrowedEntry<'g, T>)> {
+        // SAFETY: `slot_id` is always non-zero, because it includes a bit of a page.
+        let key = unsafe { Key::new_unchecked(slot_id, slot.generation()) };
+        let ptr = slot.get(key, self.guard);
+        BorrowedEntry::new(ptr).map(|entry| (key, entry))
+    }
+}
+
 impl<'g, T: 'static, C: Config> Iterator for Iter<'g, '_, T, C> {
     type Item = (Key, BorrowedEntry<'g, T>);
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some((slot, rest)) = self.slots.split_first() {
+            let slot_id = self.first_slot_id;
+
+            // It never overflows, because it's at most the id of the last slot plus one.
+            self.first_slot_id += 1;
+            self.slots = rest;
+
+            if let Some(pair) = self.entry(slot_id, slot) {
+                return Some(pair);
+            }
+        }
+
+        None
+    }
+}
+
+impl<T: 'static, C: Config> DoubleEndedIterator for Iter<'_, '_, T, C> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        while let Some((slot, rest)) = self.slots.split_last() {
+            self.slots = rest;
+
+            // It never overflows, because it's the id of a slot in the page.
+            #[allow(clippy::cast_possible_truncation)]
+            let slot_id = self.first_slot_id + rest.len() as u32;
+
+            if let Some(pair) = self.entry(slot_id, slot) {
+                return Some(pair);
+            }
+        }
+
+        None
+    }
+}
+
+impl<T: 'static, C: Config> std::iter::FusedIterator for Iter<'_, '_, T, C> {}
+
+// === IterMut ===
+
+/// Iterates mutably over occupied slots, which aren't shared by owned handles.
+#[must_use]
+pub(crate) struct IterMut<'s, T, C> {
+    slots: slice::IterMut<'s, Slot<T, C>>,
+    prev_slot_id: u32,
+}
+
+impl<'s, T: 'static, C: Config> Iterator for IterMut<'s, T, C> {
+    type Item = (Key, &'s mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for slot in self.slots.by_ref() {
             // It never overflows, because it contains the index of a previous slot.
             self.prev_slot_id += 1;
-            self.slots = rest;
 
             // SAFETY: `slot_id` is always non-zero, because it includes a bit of a page.
             let key = unsafe { Key::new_unchecked(self.prev_slot_id, slot.generation()) };
-            let ptr = slot.get(key, self.guard);
 
-            if let Some(entry) = BorrowedEntry::new(ptr) {
-                return Some((key, entry));
+            if let Some(value) = slot.get_mut(key) {
+                return Some((key, value));
             }
         }
 
@@ -264,4 +1270,4 @@ impl<'g, T: 'static, C: Config> Iterator for Iter<'g, '_, T, C> {
     }
 }
 
-impl<T: 'static, C: Config> std::iter::FusedIterator for Iter<'_, '_, T, C> {}
+impl<T: 'static, C: Config> std::iter::FusedIterator for IterMut<'_, T, C> {}
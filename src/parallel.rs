@@ -0,0 +1,184 @@
+use std::{marker::PhantomData, ops::Range};
+
+use rayon::iter::{
+    plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer},
+    IntoParallelIterator, ParallelIterator,
+};
+
+use crate::{
+    allocator::PageAllocator,
+    config::Config,
+    handles::OwnedEntry,
+    key::{IdrKey, Key},
+    page::Page,
+    EbrGuard, Idr,
+};
+
+// === ParIter ===
+
+/// A parallel iterator over all occupied entries in the IDR.
+///
+/// See [`Idr::par_iter()`] for more details.
+///
+/// [`Idr::par_iter()`]: crate::Idr::par_iter
+#[must_use]
+pub struct ParIter<'s, T, C, K = Key> {
+    pages: &'s [Page<T, C>],
+    _key: PhantomData<fn() -> K>,
+}
+
+impl<'s, T: 'static, C: Config, K: IdrKey> ParIter<'s, T, C, K> {
+    pub(crate) fn new(pages: &'s [Page<T, C>]) -> Self {
+        // Pages are allocated in order, so take only allocated ones
+        // in order to avoid splitting work by empty pages.
+        let allocated = pages.iter().take_while(|page| page.is_allocated()).count();
+
+        Self {
+            pages: &pages[..allocated],
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<T, C, K> ParallelIterator for ParIter<'_, T, C, K>
+where
+    T: Send + Sync + 'static,
+    C: Config,
+    K: IdrKey + Send,
+{
+    type Item = (K, OwnedEntry<T>);
+
+    fn drive_unindexed<R>(self, consumer: R) -> R::Result
+    where
+        R: UnindexedConsumer<Self::Item>,
+    {
+        Pages(self.pages)
+            .map(|(key, entry)| (K::from_key(key), entry))
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<'s, T, C, A, K> IntoParallelIterator for &'s Idr<T, C, A, K>
+where
+    T: Send + Sync + 'static,
+    C: Config,
+    A: PageAllocator,
+    K: IdrKey + Send,
+{
+    type Item = (K, OwnedEntry<T>);
+    type Iter = ParIter<'s, T, C, K>;
+
+    #[inline]
+    fn into_par_iter(self) -> Self::Iter {
+        self.par_iter()
+    }
+}
+
+impl<T, C, K> std::fmt::Debug for ParIter<'_, T, C, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParIter").finish_non_exhaustive()
+    }
+}
+
+// === Pages ===
+
+// Yields raw keys, which are converted by `ParIter`.
+struct Pages<'s, T, C>(&'s [Page<T, C>]);
+
+impl<T: Send + Sync + 'static, C: Config> ParallelIterator for Pages<'_, T, C> {
+    type Item = (Key, OwnedEntry<T>);
+
+    fn drive_unindexed<R>(self, consumer: R) -> R::Result
+    where
+        R: UnindexedConsumer<Self::Item>,
+    {
+        bridge_unindexed(Producer::Pages(self.0), consumer)
+    }
+}
+
+// === Producer ===
+
+// Ranges of slots smaller than this aren't split further.
+const MIN_SPLIT_LEN: u32 = 256;
+
+enum Producer<'s, T, C> {
+    Pages(&'s [Page<T, C>]),
+    Slots(&'s Page<T, C>, Range<u32>),
+}
+
+impl<T: Send + Sync + 'static, C: Config> UnindexedProducer for Producer<'_, T, C> {
+    type Item = (Key, OwnedEntry<T>);
+
+    fn split(self) -> (Self, Option<Self>) {
+        match self {
+            Self::Pages([]) => (self, None),
+            Self::Pages([page]) => Self::Slots(page, 0..page.capacity()).split(),
+            // Every page is twice as large as the previous one, so the last page
+            // is larger than all previous ones together, which is a good split point.
+            Self::Pages([rest @ .., last]) => (
+                Self::Pages(rest),
+                Some(Self::Slots(last, 0..last.capacity())),
+            ),
+            Self::Slots(page, range) => {
+                if range.len() < 2 * MIN_SPLIT_LEN as usize {
+                    return (Self::Slots(page, range), None);
+                }
+
+                let mid = range.start + (range.end - range.start) / 2;
+                (
+                    Self::Slots(page, range.start..mid),
+                    Some(Self::Slots(page, mid..range.end)),
+                )
+            }
+        }
+    }
+
+    fn fold_with<F>(self, mut folder: F) -> F
+    where
+        F: Folder<Self::Item>,
+    {
+        // `EbrGuard` cannot be sent to another thread, so every worker pins its own one.
+        let guard = EbrGuard::new();
+
+        match self {
+            Self::Pages(pages) => {
+                for page in pages {
+                    folder = fold_slots(page, 0..page.capacity(), folder, &guard);
+
+                    if folder.full() {
+                        break;
+                    }
+                }
+
+                folder
+            }
+            Self::Slots(page, range) => fold_slots(page, range, folder, &guard),
+        }
+    }
+}
+
+fn fold_slots<T, C, F>(page: &Page<T, C>, range: Range<u32>, mut folder: F, guard: &EbrGuard) -> F
+where
+    T: 'static,
+    C: Config,
+    F: Folder<(Key, OwnedEntry<T>)>,
+{
+    let Some(iter) = page.iter_range(range, guard) else {
+        return folder;
+    };
+
+    for (key, entry) in iter {
+        // Skip the entry if it has been removed concurrently.
+        let Some(entry) = entry.to_owned() else {
+            continue;
+        };
+
+        folder = folder.consume((key, entry));
+
+        if folder.full() {
+            break;
+        }
+    }
+
+    folder
+}
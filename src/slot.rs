@@ -1,9 +1,9 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ptr};
 
 use sdd::AtomicShared;
 
 use crate::{
-    config::Config,
+    config::{Config, ConfigPrivate},
     key::{Generation, Key},
     loom::{
         sync::atomic::{AtomicU32, Ordering},
@@ -12,18 +12,21 @@ use crate::{
     EbrGuard,
 };
 
+// Marks vacant slots that are never reused, see `Config::RETIRE_SLOTS_ON_WRAP`.
+const RETIRED: sdd::Tag = sdd::Tag::First;
+
 pub(crate) struct Slot<T, C> {
     generation: AtomicU32,
-    next_free: AtomicU32, // MAX means no next
-    data: AtomicShared<T>,
-    exclusive: ExclTrack, // loom only
+    next_free: AtomicU32,  // MAX means no next
+    data: AtomicShared<T>, // tagged by `RETIRED` if retired
+    exclusive: ExclTrack,  // loom only
     _config: PhantomData<C>,
 }
 
 impl<T: 'static, C: Config> Slot<T, C> {
-    pub(crate) fn new(next_free: u32) -> Self {
+    pub(crate) fn new(next_free: u32, generation: Generation<C>) -> Self {
         Self {
-            generation: AtomicU32::new(0),
+            generation: AtomicU32::new(generation.to_u32()),
             next_free: AtomicU32::new(next_free),
             data: AtomicShared::null(),
             exclusive: ExclTrack::new(),
@@ -31,8 +34,15 @@ impl<T: 'static, C: Config> Slot<T, C> {
         }
     }
 
-    pub(crate) fn init(&self, value: T) {
+    pub(crate) fn init(&self, key: Key, value: T) {
         let _track = self.exclusive.ensure();
+
+        // Vacant slots have no reserved bits, so the generation should be
+        // stored only if the key contains them. It's published by storing data.
+        if C::STORES_RESERVED_BITS && key.reserved::<C>() != 0 {
+            self.generation
+                .store(key.generation::<C>().to_u32(), Ordering::Relaxed);
+        }
         let pair = (Some(sdd::Shared::new(value)), sdd::Tag::None);
 
         // It's impossible to reach this point for the same slot concurrently.
@@ -43,52 +53,227 @@ impl<T: 'static, C: Config> Slot<T, C> {
         debug_assert!(old_data.is_none());
     }
 
-    pub(crate) fn uninit(&self, key: Key) -> bool {
-        // For now, `impl Drop for Shared` uses a special guard, which doesn't clean up.
-        // It can cause OOM if a thread is alive for a long time and doesn't use a
-        // normal guard via `Idr::get()` or directly (see `insert_remove` benchmark).
-        // TODO: create an issue in sdd. However, it's still required for `get()`.
-        let guard = EbrGuard::new();
-
+    /// Makes the slot vacant and returns the detached data.
+    ///
+    /// Returns `None` if the slot doesn't correspond to the key
+    /// or the data has been detached concurrently by another thread.
+    pub(crate) fn uninit(&self, key: Key, guard: &EbrGuard) -> Option<sdd::Shared<T>> {
         // Check if this slot corresponds to the key.
-        let ptr = self.get(key, &guard);
-        if ptr.is_null() {
-            return false;
+        let mut ptr = self.get(key, guard);
+
+        loop {
+            if ptr.is_null() {
+                return None;
+            }
+
+            match self.uninit_if(key, ptr, guard) {
+                Ok(unreachable) => return Some(unreachable),
+                // If the slot was replaced, the key can still be valid, so check it again.
+                // If the slot was removed, `get()` returns the null pointer.
+                Err(actual) => ptr = actual,
+            }
         }
+    }
+
+    /// Makes the slot vacant if it still contains the `current` data,
+    /// which must be obtained by `get()` for the same key.
+    /// Returns the detached data or the actual data for the key on failure.
+    pub(crate) fn uninit_if<'g>(
+        &self,
+        key: Key,
+        current: sdd::Ptr<'g, T>,
+        guard: &'g EbrGuard,
+    ) -> Result<sdd::Shared<T>, sdd::Ptr<'g, T>> {
+        debug_assert!(!current.is_null());
 
         // Try to replace the data pointer with the null pointer
         // in order to make it unreachable via IDR for other threads.
         //
-        // It fails if another thread removed or even replaced the same slot
-        // concurrently after this one called `get()` above.
+        // It fails if another thread removed or replaced the same slot
+        // concurrently after `current` was loaded.
         //
         // There is no ABA problem with the data pointer here because
         // the data pointer cannot be reused until the EBR guard is dropped.
+        //
+        // The slot of the last generation is retired instead of being reused,
+        // the mark is checked by `Page::add_free()`.
+        let generation = key.generation::<C>();
+        let retire = C::RETIRE_SLOTS_ON_WRAP && generation.is_last();
+        let tag = if retire { RETIRED } else { sdd::Tag::None };
+
         let Ok((unreachable, _)) = self.data.compare_exchange(
-            ptr,
-            (None, sdd::Tag::None),
+            current,
+            (None, tag),
             Ordering::AcqRel,
             Ordering::Relaxed,
             &guard.0,
         ) else {
-            // If either the slot was removed or replaced, simply return.
-            // We don't need to retry or check generation in this case.
-            return false;
+            return Err(self.get(key, guard));
         };
 
         // It's impossible to reach this point for the same slot concurrently.
         let _track = self.exclusive.ensure();
-        let _ = unreachable.unwrap().release();
 
-        // We can use `store` instead of CAS here because:
-        // * This code is executed only by one thread.
-        // * This is the only place where the generation is changed.
-        let new_generation = key.generation::<C>().inc().to_u32();
-        self.generation.store(new_generation, Ordering::Relaxed);
+        // The generation of retired slots isn't changed, so it never wraps.
+        if !retire {
+            // We can use `store` instead of CAS here because:
+            // * This code is executed only by one thread.
+            // * Only this place and `init()` change the generation.
+            let new_generation = generation.inc().to_u32();
+            self.generation.store(new_generation, Ordering::Relaxed);
+        }
+
+        // SAFETY: `current` is non-null, thus the previous data is non-null too.
+        Ok(unsafe { unreachable.unwrap_unchecked() })
+    }
+
+    /// Replaces the data if the slot corresponds to the key.
+    /// Returns the replaced data.
+    pub(crate) fn replace(&self, key: Key, value: T) -> Option<sdd::Shared<T>> {
+        let guard = EbrGuard::new();
+
+        let mut current = self.get(key, &guard);
+        if current.is_null() {
+            return None;
+        }
+
+        let mut new = sdd::Shared::new(value);
+
+        loop {
+            match self.compare_exchange(current, new, &guard) {
+                Ok(old) => return Some(old),
+                Err(returned) => new = returned,
+            }
+
+            // The slot was either removed or replaced concurrently.
+            current = self.get(key, &guard);
+            if current.is_null() {
+                return None;
+            }
+        }
+    }
+
+    /// Replaces the data if the slot corresponds to the key
+    /// and still contains the `current` data.
+    /// Returns the replaced data.
+    pub(crate) fn compare_and_swap(
+        &self,
+        key: Key,
+        current: sdd::Ptr<'_, T>,
+        value: T,
+    ) -> Option<sdd::Shared<T>> {
+        let guard = EbrGuard::new();
+
+        if current.is_null() || self.get(key, &guard) != current {
+            return None;
+        }
+
+        self.compare_exchange(current, sdd::Shared::new(value), &guard)
+            .ok()
+    }
+
+    /// Replaces the data with a value produced from the current one, retrying
+    /// if the slot is replaced concurrently. Returns the replaced data.
+    pub(crate) fn update(&self, key: Key, mut f: impl FnMut(&T) -> T) -> Option<sdd::Shared<T>> {
+        let guard = EbrGuard::new();
+
+        loop {
+            let current = self.get(key, &guard);
+            let new = sdd::Shared::new(f(current.as_ref()?));
+
+            if let Ok(old) = self.compare_exchange(current, new, &guard) {
+                return Some(old);
+            }
+        }
+    }
+
+    fn compare_exchange<'g>(
+        &self,
+        current: sdd::Ptr<'g, T>,
+        new: sdd::Shared<T>,
+        guard: &'g EbrGuard,
+    ) -> Result<sdd::Shared<T>, sdd::Shared<T>> {
+        debug_assert!(!current.is_null());
+
+        // There is no ABA problem with the data pointer here because
+        // the data pointer cannot be reused until the EBR guard is dropped.
+        // Thus, if the pointer is the same, the slot hasn't been removed,
+        // and its generation hasn't been changed since `current` was loaded.
+        match self.data.compare_exchange(
+            current,
+            (Some(new), sdd::Tag::None),
+            Ordering::AcqRel,
+            Ordering::Relaxed,
+            &guard.0,
+        ) {
+            // SAFETY: `current` is non-null, thus the previous data is non-null too.
+            Ok((old, _)) => Ok(unsafe { old.unwrap_unchecked() }),
+            // SAFETY: The provided data is always returned back on failure.
+            Err((new, _)) => Err(unsafe { new.unwrap_unchecked() }),
+        }
+    }
+
+    /// Returns a mutable reference to the data if the slot corresponds to the key
+    /// and the data isn't shared by owned handles.
+    pub(crate) fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        if key.generation::<C>() != self.generation() {
+            return None;
+        }
+
+        // Retired slots are vacant, and their mark must not be lost below.
+        if self.is_vacant() {
+            return None;
+        }
+
+        // `sdd::AtomicShared` doesn't provide mutable access to the data,
+        // so detach it temporarily. Nobody can observe it, the slot is borrowed mutably.
+        let (mut data, _) = self.data.swap((None, sdd::Tag::None), Ordering::Relaxed);
+
+        // SAFETY: Pointers without a strong reference (`BorrowedEntry`) cannot
+        // outlive a shared borrow of the IDR, thus there are no such pointers.
+        let ptr = unsafe { data.as_mut()?.get_mut() }.map(ptr::from_mut);
+
+        self.data.swap((data, sdd::Tag::None), Ordering::Relaxed);
+
+        // SAFETY: The data is owned by the slot, which is borrowed mutably,
+        // and isn't shared by owned handles, checked by `get_mut()` above.
+        ptr.map(|ptr| unsafe { &mut *ptr })
+    }
+
+    /// Sets the generation from the key and places the value, if provided.
+    /// Returns `false` if the slot is occupied.
+    pub(crate) fn restore(&mut self, key: Key, value: Option<T>) -> bool {
+        if !self.is_vacant() {
+            return false;
+        }
+
+        let generation = key.generation::<C>().to_u32();
+        self.generation.store(generation, Ordering::Relaxed);
+
+        if let Some(value) = value {
+            let pair = (Some(sdd::Shared::new(value)), sdd::Tag::None);
+            self.data.swap(pair, Ordering::Relaxed);
+        }
 
         true
     }
 
+    pub(crate) fn is_vacant(&self) -> bool {
+        self.data.is_null(Ordering::Relaxed)
+    }
+
+    /// Marks the vacant slot as retired, see `Config::RETIRE_SLOTS_ON_WRAP`.
+    /// It must not be linked into the free list.
+    #[cfg(feature = "serde")]
+    pub(crate) fn retire(&mut self) {
+        debug_assert!(self.is_vacant());
+        self.data.swap((None, RETIRED), Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_retired(&self) -> bool {
+        self.data.tag(Ordering::Relaxed) == RETIRED
+    }
+
     pub(crate) fn generation(&self) -> Generation<C> {
         let gen = self.generation.load(Ordering::Relaxed);
         Generation::<C>::new(gen)
@@ -0,0 +1,367 @@
+use std::{fmt, marker::PhantomData, num::NonZeroU64};
+
+use serde::{
+    de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, SerializeStruct, Serializer},
+};
+
+use crate::{
+    allocator::PageAllocator,
+    config::{Config, ConfigPrivate},
+    error::AllocError,
+    key::{IdrKey, Key, TypedKey},
+    page::SlotState,
+    EbrGuard, Idr,
+};
+
+// === Key ===
+
+/// Serializes the key as a raw `u64`.
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        NonZeroU64::from(*self).serialize(serializer)
+    }
+}
+
+/// Deserializes the key from a raw non-zero `u64`.
+///
+/// The key isn't validated, because its configuration is unknown.
+/// Use [`Key::try_from_raw()`] to validate keys from untrusted sources.
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        NonZeroU64::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Serializes the key as a raw `u64`, see [`Key`].
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<T> Serialize for TypedKey<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.into_key().serialize(serializer)
+    }
+}
+
+/// Deserializes the key from a raw non-zero `u64`, see [`Key`].
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de, T> Deserialize<'de> for TypedKey<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Key::deserialize(deserializer).map(Self::from_key)
+    }
+}
+
+// === Idr ===
+
+const FIELDS: &[&str] = &[
+    "slot_bits",
+    "generation_bits",
+    "entries",
+    "vacant",
+    "retired",
+];
+
+/// Serializes a snapshot of the IDR.
+///
+/// The snapshot contains all entries with their keys, generations of vacant
+/// slots to keep protecting from the ABA problem after deserialization,
+/// retired slots (see [`Config::RETIRE_SLOTS_ON_WRAP`]) and the layout of keys
+/// (`SLOT_BITS` and `GENERATION_BITS` of the config).
+///
+/// Slots are collected under a single [`EbrGuard`] first, but the snapshot
+/// isn't atomic: entries inserted or removed concurrently may be missed.
+/// However, every slot is recorded once, so the snapshot can always be
+/// deserialized.
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<T, C, A, K> Serialize for Idr<T, C, A, K>
+where
+    T: Serialize + 'static,
+    C: Config,
+    A: PageAllocator,
+    K: IdrKey,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let guard = EbrGuard::new();
+
+        // Collect slots first, because some formats require the length.
+        // Every slot is visited once, so it cannot be recorded both as
+        // an entry and as a vacant slot if it's removed concurrently.
+        let mut entries = Vec::new();
+        let mut vacant = Vec::new();
+        let mut retired = Vec::new();
+
+        for (key, state) in self.pages.iter().flat_map(|page| page.slot_states(&guard)) {
+            match state {
+                SlotState::Occupied(entry) => entries.push((key, entry)),
+                SlotState::Vacant => vacant.push(key),
+                SlotState::Retired => retired.push(key),
+            }
+        }
+
+        let entries = entries.iter().map(|(key, entry)| (*key, &**entry));
+
+        let mut state = serializer.serialize_struct("Idr", FIELDS.len())?;
+        state.serialize_field("slot_bits", &C::SLOT_BITS)?;
+        state.serialize_field("generation_bits", &C::GENERATION_BITS)?;
+        state.serialize_field("entries", &Collect(entries))?;
+        state.serialize_field("vacant", &vacant)?;
+        state.serialize_field("retired", &retired)?;
+        state.end()
+    }
+}
+
+// Serializes an iterator with the exact length as a sequence.
+struct Collect<I>(I);
+
+impl<I> Serialize for Collect<I>
+where
+    I: ExactSizeIterator + Clone,
+    I::Item: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.clone())
+    }
+}
+
+/// Deserializes a snapshot of the IDR, see the [`Serialize`] implementation.
+///
+/// Every value is restored at its original key, including the generation
+/// and, if [`Config::STRICT_RESERVED_BITS`] is enabled, reserved bits.
+/// So keys issued before serialization stay valid. If [`Config::INSTANCE_TAG`]
+/// is enabled, the instance tag of the snapshot is restored too.
+///
+/// Fails if the snapshot was taken with a config having a different layout
+/// of keys, or if it contains invalid or duplicate keys.
+///
+/// Pages are allocated up to the page of the last key, so a snapshot from
+/// an untrusted source can make the IDR allocate all its pages.
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de, T, C, A, K> Deserialize<'de> for Idr<T, C, A, K>
+where
+    T: Deserialize<'de> + 'static,
+    C: Config,
+    A: PageAllocator + Default,
+    K: IdrKey,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("Idr", FIELDS, IdrVisitor(PhantomData))
+    }
+}
+
+struct IdrVisitor<T, C, A, K>(PhantomData<(T, C, A, K)>);
+
+impl<'de, T, C, A, K> Visitor<'de> for IdrVisitor<T, C, A, K>
+where
+    T: Deserialize<'de> + 'static,
+    C: Config,
+    A: PageAllocator + Default,
+    K: IdrKey,
+{
+    type Value = Idr<T, C, A, K>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("struct Idr")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+        let slot_bits = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let generation_bits = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        check_config::<C, _>(slot_bits, generation_bits)?;
+
+        let entries = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        let vacant = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+        let retired = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(4, &self))?;
+
+        restore(entries, vacant, retired)
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Self::Value, M::Error> {
+        let mut slot_bits = None;
+        let mut generation_bits = None;
+        let mut entries = None;
+        let mut vacant = None;
+        let mut retired = None;
+
+        while let Some(field) = map.next_key::<Field>()? {
+            match field {
+                Field::SlotBits => set_field(&mut slot_bits, "slot_bits", map.next_value()?)?,
+                Field::GenerationBits => {
+                    set_field(&mut generation_bits, "generation_bits", map.next_value()?)?;
+                }
+                Field::Entries => set_field(&mut entries, "entries", map.next_value()?)?,
+                Field::Vacant => set_field(&mut vacant, "vacant", map.next_value()?)?,
+                Field::Retired => set_field(&mut retired, "retired", map.next_value()?)?,
+                Field::Unknown => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        let slot_bits = slot_bits.ok_or_else(|| de::Error::missing_field("slot_bits"))?;
+        let generation_bits =
+            generation_bits.ok_or_else(|| de::Error::missing_field("generation_bits"))?;
+        check_config::<C, _>(slot_bits, generation_bits)?;
+
+        let entries = entries.ok_or_else(|| de::Error::missing_field("entries"))?;
+        restore(
+            entries,
+            vacant.unwrap_or_default(),
+            retired.unwrap_or_default(),
+        )
+    }
+}
+
+fn set_field<V, E: de::Error>(
+    field: &mut Option<V>,
+    name: &'static str,
+    value: V,
+) -> Result<(), E> {
+    if field.replace(value).is_some() {
+        return Err(E::duplicate_field(name));
+    }
+    Ok(())
+}
+
+/// Checks that keys of the snapshot have the same layout.
+fn check_config<C: Config, E: de::Error>(slot_bits: u32, generation_bits: u32) -> Result<(), E> {
+    if slot_bits != C::SLOT_BITS || generation_bits != C::GENERATION_BITS {
+        return Err(E::custom(format_args!(
+            "incompatible config: the snapshot has SLOT_BITS={slot_bits} and \
+             GENERATION_BITS={generation_bits}, but the IDR has SLOT_BITS={} and \
+             GENERATION_BITS={}",
+            C::SLOT_BITS,
+            C::GENERATION_BITS,
+        )));
+    }
+    Ok(())
+}
+
+fn restore<T, C, A, K, E>(
+    entries: Vec<(Key, T)>,
+    vacant: Vec<Key>,
+    retired: Vec<Key>,
+) -> Result<Idr<T, C, A, K>, E>
+where
+    T: 'static,
+    C: Config,
+    A: PageAllocator + Default,
+    K: IdrKey,
+    E: de::Error,
+{
+    let mut idr = Idr::new_in(A::default());
+
+    // Keys of entries contain the instance tag of the serialized IDR, so it's
+    // adopted to keep them valid. Vacant slots don't store tags.
+    if C::INSTANCE_TAG {
+        if let Some((key, _)) = entries.first() {
+            idr.tag = key.reserved::<C>();
+        }
+    }
+
+    let entries = entries.into_iter().map(|(key, value)| (key, Some(value)));
+    let vacant = vacant.into_iter().map(|key| (key, None));
+
+    for (key, value) in entries.chain(vacant) {
+        let key = parse_key::<C, E>(key)?;
+        let raw = u64::from(key);
+
+        if value.is_some() && !idr.owns(key) {
+            return Err(E::custom(format_args!(
+                "invalid key {raw}: another instance tag"
+            )));
+        }
+
+        let page_no = key.page_no::<C>();
+        idr.allocate_pages(page_no)
+            .map_err(|AllocError| E::custom("failed to allocate memory"))?;
+
+        if !idr.pages[page_no.to_usize()].restore(key, value) {
+            return Err(E::custom(format_args!("duplicate key {raw}")));
+        }
+    }
+
+    // Retired slots are restored even if the config doesn't retire slots,
+    // so stale keys of their last generation never resolve.
+    for key in retired {
+        let key = parse_key::<C, E>(key)?;
+        let raw = u64::from(key);
+
+        let page_no = key.page_no::<C>();
+        idr.allocate_pages(page_no)
+            .map_err(|AllocError| E::custom("failed to allocate memory"))?;
+
+        if !idr.pages[page_no.to_usize()].restore_retired(key) {
+            return Err(E::custom(format_args!("duplicate key {raw}")));
+        }
+    }
+
+    for page in &mut *idr.pages {
+        page.rebuild_free_list();
+    }
+
+    Ok(idr)
+}
+
+fn parse_key<C: Config, E: de::Error>(key: Key) -> Result<Key, E> {
+    let raw = u64::from(key);
+    Key::try_from_raw::<C>(raw).map_err(|err| E::custom(format_args!("invalid key {raw}: {err}")))
+}
+
+// === Field ===
+
+enum Field {
+    SlotBits,
+    GenerationBits,
+    Entries,
+    Vacant,
+    Retired,
+    Unknown,
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(FieldVisitor)
+    }
+}
+
+struct FieldVisitor;
+
+impl Visitor<'_> for FieldVisitor {
+    type Value = Field;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a field identifier")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(match value {
+            0 => Field::SlotBits,
+            1 => Field::GenerationBits,
+            2 => Field::Entries,
+            3 => Field::Vacant,
+            4 => Field::Retired,
+            _ => Field::Unknown,
+        })
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(match value {
+            "slot_bits" => Field::SlotBits,
+            "generation_bits" => Field::GenerationBits,
+            "entries" => Field::Entries,
+            "vacant" => Field::Vacant,
+            "retired" => Field::Retired,
+            _ => Field::Unknown,
+        })
+    }
+}
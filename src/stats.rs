@@ -0,0 +1,81 @@
+use std::alloc::Layout;
+
+// === Stats ===
+
+/// A snapshot of the IDR's occupancy and memory usage.
+///
+/// See [`Idr::stats()`] for more details.
+///
+/// [`Idr::stats()`]: crate::Idr::stats
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The number of allocated pages.
+    pub allocated_pages: usize,
+    /// The total number of slots in allocated pages.
+    pub capacity: usize,
+    /// The number of occupied (or reserved) slots in allocated pages.
+    pub occupied: usize,
+    /// The number of retired slots in allocated pages, which are never reused,
+    /// see [`Config::RETIRE_SLOTS_ON_WRAP`].
+    ///
+    /// [`Config::RETIRE_SLOTS_ON_WRAP`]: crate::Config::RETIRE_SLOTS_ON_WRAP
+    pub retired: usize,
+    /// The number of free slots in allocated pages.
+    pub free: usize,
+    /// The number of bytes used by slot arrays of allocated pages.
+    pub slots_bytes: usize,
+    /// An estimated number of bytes held by EBR containers of occupied slots.
+    pub containers_bytes: usize,
+    /// Per-page statistics of allocated pages, ordered by page number.
+    pub pages: Vec<PageStats>,
+}
+
+impl Stats {
+    pub(crate) fn add_page(&mut self, page: PageStats) {
+        self.allocated_pages += 1;
+        self.capacity += page.capacity;
+        self.occupied += page.occupied;
+        self.retired += page.retired;
+        self.free += page.free;
+        self.slots_bytes += page.slots_bytes;
+        self.containers_bytes += page.containers_bytes;
+        self.pages.push(page);
+    }
+}
+
+// === PageStats ===
+
+/// A snapshot of an allocated page's occupancy and memory usage.
+///
+/// See [`Idr::stats()`] for more details.
+///
+/// [`Idr::stats()`]: crate::Idr::stats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PageStats {
+    /// The number of the page.
+    pub page_no: usize,
+    /// The number of slots in the page.
+    pub capacity: usize,
+    /// The number of occupied (or reserved) slots in the page.
+    pub occupied: usize,
+    /// The number of retired slots in the page, which are never reused.
+    pub retired: usize,
+    /// The number of free slots in the page.
+    pub free: usize,
+    /// The number of bytes used by the slot array of the page.
+    pub slots_bytes: usize,
+    /// An estimated number of bytes held by EBR containers of occupied slots.
+    pub containers_bytes: usize,
+}
+
+/// Returns an estimated size of the EBR container holding a value of type `T`.
+///
+/// `sdd` stores a value along with a reference counter and a link to the next
+/// retired container, which take two words together.
+pub(crate) fn container_size<T>() -> usize {
+    Layout::new::<T>()
+        .extend(Layout::new::<[usize; 2]>())
+        .map_or(0, |(layout, _)| layout.pad_to_align().size())
+}
@@ -0,0 +1,121 @@
+use std::{
+    alloc::Layout,
+    panic::{self, AssertUnwindSafe},
+    ptr::NonNull,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use idr_ebr::{Config, EbrGuard, Global, Idr, InsertError, PageAllocator};
+
+struct TinyConfig;
+impl Config for TinyConfig {
+    const INITIAL_PAGE_SIZE: u32 = 4;
+    const MAX_PAGES: u32 = 5;
+    const RESERVED_BITS: u32 = 32;
+}
+
+#[derive(Default)]
+struct TestAllocator {
+    allocated: AtomicUsize,
+    deallocated: AtomicUsize,
+    fail: AtomicBool,
+    panic: AtomicBool,
+}
+
+impl TestAllocator {
+    fn live(&self) -> usize {
+        self.allocated.load(Ordering::Relaxed) - self.deallocated.load(Ordering::Relaxed)
+    }
+}
+
+// SAFETY: Delegates to the global allocator.
+unsafe impl PageAllocator for TestAllocator {
+    fn allocate(&self, layout: Layout) -> Option<NonNull<u8>> {
+        assert!(!self.panic.load(Ordering::Relaxed), "allocator panics");
+
+        if self.fail.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        self.allocated.fetch_add(1, Ordering::Relaxed);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.deallocated.fetch_add(1, Ordering::Relaxed);
+        // SAFETY: Guaranteed by the caller.
+        unsafe { Global.deallocate(ptr, layout) };
+    }
+}
+
+#[test]
+fn counting() {
+    let alloc = TestAllocator::default();
+
+    let mut idr = Idr::<u64, TinyConfig, _>::new_in(&alloc);
+    assert_eq!(alloc.live(), 0);
+
+    let keys = (0..28).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    assert_eq!(alloc.live(), 3);
+    assert_eq!(idr.allocator().live(), 3);
+
+    for key in &keys[1..] {
+        assert!(idr.remove(*key));
+    }
+
+    idr.shrink_to_fit();
+    assert_eq!(alloc.live(), 1);
+    assert_eq!(alloc.deallocated.load(Ordering::Relaxed), 2);
+
+    drop(idr);
+    assert_eq!(alloc.live(), 0);
+    assert_eq!(alloc.allocated.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn with_capacity_in() {
+    let alloc = TestAllocator::default();
+
+    let idr = Idr::<u64, TinyConfig, _>::with_capacity_in(10, &alloc);
+    assert_eq!(alloc.live(), 2);
+
+    drop(idr);
+    assert_eq!(alloc.live(), 0);
+}
+
+#[test]
+fn failing() {
+    let alloc = TestAllocator::default();
+    alloc.fail.store(true, Ordering::Relaxed);
+
+    let idr = Idr::<String, TinyConfig, _>::new_in(&alloc);
+
+    let error = idr.try_insert(String::from("foo")).unwrap_err();
+    assert_eq!(error, InsertError::AllocFailed(String::from("foo")));
+    assert_eq!(idr.stats().allocated_pages, 0);
+
+    alloc.fail.store(false, Ordering::Relaxed);
+
+    let key = idr.try_insert(error.into_inner()).unwrap();
+    assert_eq!(idr.get(key, &EbrGuard::new()).unwrap(), String::from("foo"));
+    assert_eq!(alloc.live(), 1);
+}
+
+#[test]
+fn panicking() {
+    let alloc = TestAllocator::default();
+    alloc.panic.store(true, Ordering::Relaxed);
+
+    let idr = Idr::<u64, TinyConfig, _>::new_in(&alloc);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| idr.insert(42)));
+    assert!(result.is_err());
+    assert_eq!(idr.stats().allocated_pages, 0);
+
+    // The IDR is still usable after the allocator panics.
+    alloc.panic.store(false, Ordering::Relaxed);
+
+    let key = idr.insert(42).unwrap();
+    assert_eq!(idr.get(key, &EbrGuard::new()).unwrap(), 42);
+    assert_eq!(alloc.live(), 1);
+}
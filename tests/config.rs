@@ -34,4 +34,6 @@ fn invalid() {
     t.compile_fail("tests/config/reserved_bits_too_big.rs");
     t.compile_fail("tests/config/slot_bits_too_big.rs");
     t.compile_fail("tests/config/generation_bits_too_big.rs");
+    t.compile_fail("tests/config/strict_slot_bits_too_small.rs");
+    t.compile_fail("tests/config/instance_tag_without_reserved_bits.rs");
 }
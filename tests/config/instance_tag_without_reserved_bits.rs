@@ -0,0 +1,10 @@
+use idr_ebr::{Config, Idr};
+
+struct InvalidConfig;
+impl Config for InvalidConfig {
+    const INSTANCE_TAG: bool = true;
+}
+
+fn main() {
+    let _ = Idr::<u64, InvalidConfig>::new();
+}
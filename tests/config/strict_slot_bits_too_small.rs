@@ -0,0 +1,12 @@
+use idr_ebr::{Config, Idr};
+
+struct InvalidConfig;
+impl Config for InvalidConfig {
+    const MAX_PAGES: u32 = 20;
+    const RESERVED_BITS: u32 = 8;
+    const STRICT_RESERVED_BITS: bool = true;
+}
+
+fn main() {
+    let _ = Idr::<u64, InvalidConfig>::new();
+}
@@ -1,4 +1,4 @@
-use idr_ebr::{Config, EbrGuard, Idr};
+use idr_ebr::{Config, EbrGuard, Idr, InsertAtError, InsertError, InvalidKey, Key, ReusePolicy};
 
 #[test]
 fn few_slots() {
@@ -66,3 +66,564 @@ fn few_generations() {
     assert_ne!(key3, key2);
     assert_eq!(key3, key);
 }
+
+#[test]
+fn try_insert_full() {
+    struct OneSlotConfig;
+    impl Config for OneSlotConfig {
+        const INITIAL_PAGE_SIZE: u32 = 1;
+        const MAX_PAGES: u32 = 1;
+        const RESERVED_BITS: u32 = 32;
+    }
+
+    let idr = Idr::<String, OneSlotConfig>::new();
+
+    let key = idr.try_insert(String::from("foo")).unwrap();
+
+    let error = idr.try_insert(String::from("bar")).unwrap_err();
+    assert!(matches!(error, InsertError::Full(_)));
+    assert_eq!(error.to_string(), "no free slots in the IDR");
+    assert_eq!(format!("{error:?}"), "Full(..)");
+    assert_eq!(error.into_inner(), String::from("bar"));
+
+    let error = idr.try_vacant_entry().unwrap_err();
+    assert_eq!(error, InsertError::Full(()));
+
+    assert!(idr.remove(key));
+    let key = idr.try_insert(String::from("baz")).unwrap();
+    assert_eq!(idr.get(key, &EbrGuard::new()).unwrap(), String::from("baz"));
+}
+
+#[test]
+fn insert_many_full() {
+    struct FewSlotsConfig;
+    impl Config for FewSlotsConfig {
+        const INITIAL_PAGE_SIZE: u32 = 1;
+        const MAX_PAGES: u32 = 4;
+        const RESERVED_BITS: u32 = 32;
+    }
+
+    let idr = Idr::<u64, FewSlotsConfig>::new();
+
+    let keys = idr.insert_many(0..20);
+    assert_eq!(keys.len(), 15);
+    assert!(idr.insert_many(0..20).is_empty());
+
+    let guard = EbrGuard::new();
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(idr.get(*key, &guard).unwrap(), i as u64);
+    }
+}
+
+#[test]
+fn decode_keys() {
+    struct SmallConfig;
+    impl Config for SmallConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 3;
+        const RESERVED_BITS: u32 = 32;
+    }
+
+    let idr = Idr::<u64, SmallConfig>::new();
+    let keys = (0..28).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+
+    let mut expected = Vec::new();
+    for (page_no, page_size) in [(0, 4), (1, 8), (2, 16)] {
+        expected.extend((0..page_size).map(|slot_index| (page_no, slot_index)));
+    }
+
+    let mut actual = keys
+        .iter()
+        .map(|key| {
+            let parts = key.decode::<SmallConfig>();
+            assert_eq!(parts.reserved, 0);
+            assert_eq!(parts.generation, 0);
+            (parts.page_no.unwrap(), parts.slot_index.unwrap())
+        })
+        .collect::<Vec<_>>();
+    actual.sort_unstable();
+    assert_eq!(actual, expected);
+
+    for &key in &keys {
+        let raw = u64::from(key);
+        assert_eq!(Key::try_from_raw::<SmallConfig>(raw), Ok(key));
+
+        // Reserved bits are preserved.
+        let key = Key::try_from_raw::<SmallConfig>(raw | (0xdead << 32)).unwrap();
+        assert_eq!(key.decode::<SmallConfig>().reserved, 0xdead);
+    }
+
+    // The generation is incremented on reuse.
+    assert!(idr.remove(keys[0]));
+    let key = idr.insert(0).unwrap();
+    assert_eq!(key.decode::<SmallConfig>().generation, 1);
+
+    // Slot ids below the initial page size don't refer to any page.
+    for raw in 1..4 {
+        let error = Key::try_from_raw::<SmallConfig>(raw).unwrap_err();
+        assert_eq!(error, InvalidKey::NoPage);
+
+        let parts = Key::try_from(raw).unwrap().decode::<SmallConfig>();
+        assert_eq!((parts.page_no, parts.slot_index), (None, None));
+    }
+
+    let error = Key::try_from_raw::<SmallConfig>(0).unwrap_err();
+    assert_eq!(error, InvalidKey::Zero);
+    assert_eq!(error.to_string(), "the key is zero");
+}
+
+#[test]
+fn reserved_bits() {
+    struct ReservedConfig;
+    impl Config for ReservedConfig {
+        const RESERVED_BITS: u32 = 8;
+    }
+
+    let idr = Idr::<u64, ReservedConfig>::new();
+    let guard = EbrGuard::new();
+
+    let key = idr.insert_with_reserved(1, 0xab).unwrap();
+    assert_eq!(key.reserved::<ReservedConfig>(), 0xab);
+    assert_eq!(
+        key.with_reserved::<ReservedConfig>(0)
+            .reserved::<ReservedConfig>(),
+        0
+    );
+
+    // Reserved bits are ignored by default.
+    let other = key.with_reserved::<ReservedConfig>(0xff);
+    assert_eq!(idr.get(other, &guard).unwrap(), 1);
+    assert_eq!(
+        idr.iter(&guard)
+            .next()
+            .unwrap()
+            .0
+            .reserved::<ReservedConfig>(),
+        0
+    );
+    assert!(idr.remove(other));
+
+    let result = std::panic::catch_unwind(|| key.with_reserved::<ReservedConfig>(0x100));
+    assert!(result.is_err());
+}
+
+#[test]
+fn strict_reserved_bits() {
+    struct StrictConfig;
+    impl Config for StrictConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 30;
+        const RESERVED_BITS: u32 = 8;
+        const STRICT_RESERVED_BITS: bool = true;
+    }
+
+    let idr = Idr::<u64, StrictConfig>::new();
+    let guard = EbrGuard::new();
+
+    let key = idr.insert_with_reserved(1, 0xab).unwrap();
+    let plain = idr.insert(2).unwrap();
+    assert_eq!(key.reserved::<StrictConfig>(), 0xab);
+    assert_eq!(plain.reserved::<StrictConfig>(), 0);
+
+    // Only keys with the same reserved bits are accepted.
+    assert_eq!(idr.get(key, &guard).unwrap(), 1);
+    assert_eq!(idr.get(plain, &guard).unwrap(), 2);
+    assert!(!idr.contains(key.with_reserved::<StrictConfig>(0)));
+    assert!(!idr.contains(plain.with_reserved::<StrictConfig>(0xab)));
+    assert!(!idr.remove(key.with_reserved::<StrictConfig>(0xff)));
+
+    // Iterators return keys with stored reserved bits.
+    let mut keys = idr.iter(&guard).map(|(key, _)| key).collect::<Vec<_>>();
+    keys.sort_unstable();
+    let mut expected = vec![key, plain];
+    expected.sort_unstable();
+    assert_eq!(keys, expected);
+
+    // The slot is reused without reserved bits, but with the next generation.
+    assert!(idr.remove(key));
+    let new_key = idr.insert(3).unwrap();
+    let (parts, new_parts) = (
+        key.decode::<StrictConfig>(),
+        new_key.decode::<StrictConfig>(),
+    );
+    assert_eq!(new_parts.reserved, 0);
+    assert_eq!(new_parts.generation, parts.generation + 1);
+    assert_eq!(new_parts.slot_index, parts.slot_index);
+    assert!(idr.get(key, &guard).is_none());
+    assert!(idr
+        .get(key.with_reserved::<StrictConfig>(0), &guard)
+        .is_none());
+    assert_eq!(idr.get(new_key, &guard).unwrap(), 3);
+}
+
+#[test]
+fn instance_tag() {
+    struct TaggedConfig;
+    impl Config for TaggedConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 30;
+        const RESERVED_BITS: u32 = 8;
+        const INSTANCE_TAG: bool = true;
+    }
+
+    let mut idr = Idr::<u64, TaggedConfig>::new();
+    let tag = idr.instance_tag().unwrap();
+    assert!(tag != 0 && tag <= 0xff);
+    assert_eq!(Idr::<u64>::new().instance_tag(), None);
+
+    // Every produced key is stamped.
+    let mut keys = vec![idr.insert(0).unwrap()];
+    keys.extend(idr.insert_many(1..10));
+    let entry = idr.vacant_entry().unwrap();
+    keys.push(entry.key());
+    entry.insert(10);
+    assert!(keys.iter().all(|key| key.reserved::<TaggedConfig>() == tag));
+
+    let guard = EbrGuard::new();
+    let visited = idr.iter(&guard).map(|(key, _)| key).collect::<Vec<_>>();
+    assert_eq!(visited, keys);
+
+    // Keys with another tag are rejected.
+    let foreign = keys[0].with_reserved::<TaggedConfig>(tag % 0xff + 1);
+    assert!(idr.get(foreign, &guard).is_none());
+    assert!(idr.replace(foreign, 100).is_none());
+    assert!(!idr.remove(foreign));
+    assert!(idr.take(foreign).is_none());
+    assert_eq!(idr.remove_many(&[foreign]), 0);
+    assert_eq!(idr.get(keys[0], &guard).unwrap(), 0);
+    drop(guard);
+    assert!(idr.get_mut(foreign).is_none());
+    assert_eq!(
+        idr.insert_at(foreign, 100),
+        Err(InsertAtError::InvalidKey(100))
+    );
+
+    // Slots are reused with the same tag, but the next generation.
+    assert!(idr.remove(keys[0]));
+    let new_key = idr.insert(11).unwrap();
+    let parts = new_key.decode::<TaggedConfig>();
+    assert_eq!(parts.reserved, tag);
+    assert_eq!(parts.generation, 1);
+    assert!(!idr.contains(keys[0]));
+
+    // Instances draw different tags, at least eventually.
+    assert!((0..100).any(|_| Idr::<u64, TaggedConfig>::new().instance_tag() != Some(tag)));
+
+    // A replica adopts the tag of the primary to accept its keys.
+    let mut replica = Idr::<u64, TaggedConfig>::new().with_instance_tag(tag);
+    assert_eq!(replica.instance_tag(), Some(tag));
+    replica.insert_at(new_key, 11).unwrap();
+    assert_eq!(replica.get(new_key, &EbrGuard::new()).unwrap(), 11);
+    assert_eq!(replica.insert(12).unwrap().reserved::<TaggedConfig>(), tag);
+
+    let result =
+        std::panic::catch_unwind(|| Idr::<u64, TaggedConfig>::new().with_instance_tag(0x100));
+    assert!(result.is_err());
+}
+
+#[test]
+fn insert_at_full() {
+    struct TinyConfig;
+    impl Config for TinyConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 3;
+        const RESERVED_BITS: u32 = 32;
+    }
+
+    // 5 bits for slot ids, the rest for generations.
+    let key = |slot_id: u64, generation: u64| {
+        Key::try_from_raw::<TinyConfig>(generation << 5 | slot_id).unwrap()
+    };
+
+    let mut idr = Idr::<u64, TinyConfig>::new();
+
+    // The first, middle and last slots of the first and last pages.
+    let placed = [
+        key(4, 3),
+        key(6, 0),
+        key(7, 1),
+        key(16, 7),
+        key(20, 2),
+        key(31, 5),
+    ];
+    for (i, &key) in placed.iter().enumerate() {
+        idr.insert_at(key, i as u64).unwrap();
+    }
+
+    let error = idr.insert_at(placed[1], 42).unwrap_err();
+    assert_eq!(error, InsertAtError::Occupied(42));
+    let error = idr.insert_at(Key::try_from(1).unwrap(), 42).unwrap_err();
+    assert_eq!(error, InsertAtError::InvalidKey(42));
+
+    let guard = EbrGuard::new();
+    for (i, &key) in placed.iter().enumerate() {
+        assert_eq!(idr.get(key, &guard).unwrap(), i as u64);
+    }
+    assert!(!idr.contains(key(4, 2)));
+    assert!(!idr.contains(key(16, 0)));
+    drop(guard);
+
+    // Other slots are still available, but placed ones aren't reused.
+    let mut keys = placed.to_vec();
+    while let Some(key) = idr.insert(0) {
+        assert!(!keys.contains(&key));
+        keys.push(key);
+    }
+    assert_eq!(keys.len(), 28);
+    assert_eq!(idr.len(), 28);
+
+    // Removed slots are reused with the next generation.
+    assert!(idr.remove(placed[0]));
+    assert_eq!(idr.insert(0).unwrap(), key(4, 4));
+}
+
+#[test]
+fn retire_slots_on_wrap() {
+    struct RetiringConfig;
+    impl Config for RetiringConfig {
+        const MAX_PAGES: u32 = 26;
+        const RESERVED_BITS: u32 = 32;
+        const RETIRE_SLOTS_ON_WRAP: bool = true;
+    }
+
+    // 31 bits for slot ids, 1 bit for generations.
+    let mut idr = Idr::<u64, RetiringConfig>::new();
+
+    let keys = idr.insert_many(0..32); // generation=0
+    assert!(keys.iter().all(|key| idr.remove(*key)));
+    assert_eq!(idr.retired(), 0);
+
+    let keys2 = idr.insert_many(0..32); // generation=1
+    assert!(keys.iter().all(|key| !keys2.contains(key)));
+    assert!(keys2.iter().all(|key| idr.remove(*key)));
+
+    // Slots of the last generation aren't reused.
+    assert_eq!(idr.retired(), 32);
+    assert!(idr.is_empty());
+    assert!(keys.iter().chain(&keys2).all(|key| !idr.contains(*key)));
+
+    let error = idr.insert_at(keys[0], 42).unwrap_err();
+    assert_eq!(error, InsertAtError::Occupied(42));
+
+    let key = idr.insert(0).unwrap();
+    assert_eq!(key.decode::<RetiringConfig>().page_no, Some(1));
+    assert!(idr.remove(key));
+
+    let stats = idr.stats();
+    assert_eq!(stats.capacity, 96);
+    assert_eq!(stats.occupied, 0);
+    assert_eq!(stats.retired, 32);
+    assert_eq!(stats.free, 64);
+    assert_eq!(stats.pages[0].retired, 32);
+    assert_eq!(stats.pages[0].free, 0);
+
+    // Pages with retired slots aren't deallocated.
+    idr.shrink_to_fit();
+    assert_eq!(idr.stats().allocated_pages, 1);
+}
+
+#[test]
+fn retire_slots_on_wrap_partially() {
+    struct RetiringConfig;
+    impl Config for RetiringConfig {
+        const MAX_PAGES: u32 = 25;
+        const RESERVED_BITS: u32 = 32;
+        const RETIRE_SLOTS_ON_WRAP: bool = true;
+    }
+
+    // 30 bits for slot ids, 2 bits for generations.
+    let idr = Idr::<u64, RetiringConfig>::new();
+
+    let first = idr.insert(0).unwrap();
+    let other = idr.insert(1).unwrap();
+
+    let mut key = first;
+    for generation in 0..4 {
+        assert_eq!(key.decode::<RetiringConfig>().generation, generation);
+        assert!(idr.remove(key));
+        key = idr.insert(0).unwrap();
+    }
+
+    // The first slot is retired, so another free slot is taken instead.
+    assert_eq!(idr.retired(), 1);
+    let parts = key.decode::<RetiringConfig>();
+    assert_ne!(
+        parts.slot_index,
+        first.decode::<RetiringConfig>().slot_index
+    );
+    assert!(idr.contains(other));
+
+    let stats = idr.stats();
+    assert_eq!((stats.occupied, stats.retired, stats.free), (2, 1, 29));
+}
+
+#[test]
+fn retire_slots_on_wrap_concurrent_stats() {
+    struct RetiringConfig;
+    impl Config for RetiringConfig {
+        const MAX_PAGES: u32 = 26;
+        const RESERVED_BITS: u32 = 32;
+        const RETIRE_SLOTS_ON_WRAP: bool = true;
+    }
+
+    // 31 bits for slot ids, 1 bit for generations.
+    let mut idr = Idr::<u64, RetiringConfig>::new();
+
+    // Fill the first page with entries of the last generation.
+    let keys = idr.insert_many(0..32);
+    assert!(keys.iter().all(|key| idr.remove(*key)));
+    let keys = idr.insert_many(0..32);
+    assert_eq!(idr.stats().pages[0].free, 0);
+
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            for key in keys {
+                assert!(idr.remove(key));
+            }
+        });
+
+        for _ in 0..1000 {
+            // The slot being retired can be counted twice or not at all.
+            let page = idr.stats().pages[0];
+            assert!(page.free <= 1, "{page:?}");
+            idr.reserve(1);
+        }
+    });
+
+    let page = idr.stats().pages[0];
+    assert_eq!((page.occupied, page.retired, page.free), (0, 32, 0));
+
+    // The first page is full of retired slots, so `reserve()` allocates another one.
+    assert_eq!(idr.stats().allocated_pages, 2);
+    idr.shrink_to_fit();
+    assert_eq!(idr.stats().allocated_pages, 1);
+}
+
+#[test]
+fn reuse_policy_lifo() {
+    struct LifoConfig;
+    impl Config for LifoConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 1;
+        const RESERVED_BITS: u32 = 32;
+    }
+
+    let slot_index = |key: Key| key.decode::<LifoConfig>().slot_index.unwrap();
+
+    let idr = Idr::<u64, LifoConfig>::new();
+    let keys = idr.insert_many(0..4);
+
+    assert!(idr.remove(keys[1]));
+    assert!(idr.remove(keys[3]));
+
+    // The most recently freed slot is reused first.
+    assert_eq!(slot_index(idr.insert(0).unwrap()), 3);
+    assert_eq!(slot_index(idr.insert(0).unwrap()), 1);
+    assert!(idr.insert(0).is_none());
+}
+
+#[test]
+fn reuse_policy_fifo() {
+    struct FifoConfig;
+    impl Config for FifoConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 1;
+        const RESERVED_BITS: u32 = 32;
+        const REUSE_POLICY: ReusePolicy = ReusePolicy::Fifo;
+    }
+
+    let slot_index = |key: Key| key.decode::<FifoConfig>().slot_index.unwrap();
+
+    let mut idr = Idr::<u64, FifoConfig>::new();
+    let keys = idr.insert_many(0..4);
+
+    assert!(idr.remove(keys[1]));
+    assert!(idr.remove(keys[3]));
+
+    // The least recently freed slot is reused first.
+    let key = idr.insert(0).unwrap();
+    assert_eq!(slot_index(key), 1);
+    assert!(!idr.contains(keys[1]));
+
+    assert!(idr.remove(keys[2]));
+    assert_eq!(slot_index(idr.insert(0).unwrap()), 3);
+    assert_eq!(slot_index(idr.insert(0).unwrap()), 2);
+    assert!(idr.insert(0).is_none());
+
+    // Freed slots can be taken by `insert_at()` regardless of the policy.
+    assert!(idr.remove(keys[0]));
+    assert!(idr.remove(key));
+    idr.insert_at(keys[0], 42).unwrap();
+    assert_eq!(idr.get(keys[0], &EbrGuard::new()).unwrap(), 42);
+    assert_eq!(slot_index(idr.insert(0).unwrap()), 1);
+    assert!(idr.insert(0).is_none());
+}
+
+#[test]
+fn reuse_policy_quarantine() {
+    struct QuarantineConfig;
+    impl Config for QuarantineConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 1;
+        const RESERVED_BITS: u32 = 32;
+        const REUSE_POLICY: ReusePolicy = ReusePolicy::Quarantine;
+    }
+
+    let idr = Idr::<u64, QuarantineConfig>::new();
+    let keys = idr.insert_many(0..4);
+
+    // The slot isn't reused while a guard existing at the removal is alive.
+    let guard = EbrGuard::new();
+    assert!(idr.remove(keys[2]));
+
+    for _ in 0..100 {
+        assert!(idr.insert(0).is_none());
+        drop(EbrGuard::new());
+    }
+
+    assert_eq!(idr.get(keys[0], &guard).unwrap(), 0);
+    drop(guard);
+
+    // It's reused once a grace period has passed, but guards of concurrent
+    // tests can delay it.
+    let key = (0..1000)
+        .find_map(|_| {
+            sdd::Guard::new().accelerate();
+            idr.insert(42)
+        })
+        .expect("the slot isn't released");
+
+    assert_eq!(key.decode::<QuarantineConfig>().slot_index, Some(2));
+    assert_eq!(key.decode::<QuarantineConfig>().generation, 1);
+    assert!(!idr.contains(keys[2]));
+    assert!(idr.insert(0).is_none());
+}
+
+#[test]
+fn reuse_policy_quarantine_insert_at() {
+    struct QuarantineConfig;
+    impl Config for QuarantineConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 1;
+        const RESERVED_BITS: u32 = 32;
+        const REUSE_POLICY: ReusePolicy = ReusePolicy::Quarantine;
+    }
+
+    let mut idr = Idr::<u64, QuarantineConfig>::new();
+    let keys = idr.insert_many(0..4);
+
+    // Both pending and quarantined slots can be taken by `insert_at()`.
+    let guard = EbrGuard::new();
+    assert!(idr.remove(keys[0]));
+    assert!(idr.insert(0).is_none());
+    assert!(idr.remove(keys[1]));
+    drop(guard);
+
+    for (i, key) in keys[..2].iter().enumerate() {
+        idr.insert_at(*key, 10 + i as u64).unwrap();
+        assert_eq!(idr.get(*key, &EbrGuard::new()).unwrap(), 10 + i as u64);
+    }
+
+    assert!(idr.insert(0).is_none());
+    assert_eq!(idr.len(), 4);
+}
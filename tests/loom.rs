@@ -7,7 +7,7 @@ use loom::{
     thread,
 };
 
-use idr_ebr::{Config, EbrGuard, Idr, Key};
+use idr_ebr::{Config, EbrGuard, Idr, Key, ReusePolicy};
 
 // === Helpers ===
 
@@ -66,6 +66,24 @@ impl crate::Config for TinierConfig {
     const RESERVED_BITS: u32 = 32;
 }
 
+struct RetiringConfig;
+
+impl crate::Config for RetiringConfig {
+    const INITIAL_PAGE_SIZE: u32 = 2;
+    const MAX_PAGES: u32 = 30;
+    const RESERVED_BITS: u32 = 32;
+    const RETIRE_SLOTS_ON_WRAP: bool = true;
+}
+
+struct FifoConfig;
+
+impl crate::Config for FifoConfig {
+    const INITIAL_PAGE_SIZE: u32 = 2;
+    const MAX_PAGES: u32 = 1;
+    const RESERVED_BITS: u32 = 32;
+    const REUSE_POLICY: ReusePolicy = ReusePolicy::Fifo;
+}
+
 // Concurrent `VacantEntry::insert()` and `get()` on the same entry.
 #[test]
 fn vacant_entry() {
@@ -393,6 +411,105 @@ fn racy_remove_guarded() {
     });
 }
 
+// Threads take the same entry.
+#[test]
+fn racy_take() {
+    run_model(|| {
+        let idr = Arc::new(Idr::default());
+        let key = idr.insert(1).unwrap();
+
+        let idr1 = idr.clone();
+        let t1 = thread::spawn(move || show!(idr1.take(key)).map(|entry| *entry));
+
+        let idr2 = idr.clone();
+        let t2 = thread::spawn(move || show!(idr2.remove(key)));
+
+        let r1 = t1.join().unwrap();
+        let r2 = t2.join().unwrap();
+
+        assert!(r1.is_some() ^ r2, "exactly one thread removed the entry");
+        assert!(r1.is_none() || r1 == Some(1));
+        assert!(idr.get(key, &EbrGuard::new()).is_none());
+    });
+}
+
+// Concurrent `replace()`, `get()` and `remove()` on the same entry.
+#[test]
+fn racy_replace_remove() {
+    run_model(|| {
+        let idr = Arc::new(Idr::default());
+        let key = idr.insert(1).unwrap();
+
+        let idr1 = idr.clone();
+        let t1 = thread::spawn(move || show!(idr1.replace(key, 2)).map(|entry| *entry));
+
+        let idr2 = idr.clone();
+        let t2 = thread::spawn(move || {
+            let guard = EbrGuard::new();
+            let entry = show!(idr2.get(key, &guard)).map(|entry| *entry);
+            assert!(matches!(entry, None | Some(1 | 2)));
+        });
+
+        let idr3 = idr.clone();
+        let t3 = thread::spawn(move || show!(idr3.remove(key)));
+
+        let replaced = t1.join().unwrap();
+        t2.join().unwrap();
+        let removed = t3.join().unwrap();
+
+        assert!(removed, "replacing doesn't prevent removal");
+        assert!(replaced.is_none() || replaced == Some(1));
+        assert!(idr.get(key, &EbrGuard::new()).is_none());
+    });
+}
+
+// Threads update the same entry.
+#[test]
+fn racy_update() {
+    run_model(|| {
+        let idr = Arc::new(Idr::default());
+        let key = idr.insert(0).unwrap();
+
+        let idr1 = idr.clone();
+        let t1 = thread::spawn(move || show!(idr1.update(key, |v| v + 1)).map(|entry| *entry));
+
+        let idr2 = idr.clone();
+        let t2 = thread::spawn(move || show!(idr2.update(key, |v| v + 1)).map(|entry| *entry));
+
+        let r1 = t1.join().unwrap().unwrap();
+        let r2 = t2.join().unwrap().unwrap();
+
+        assert_ne!(r1, r2, "no lost updates");
+        assert_eq!(idr.get(key, &EbrGuard::new()).unwrap(), 2);
+    });
+}
+
+// One thread retains entries, and another one replaces them concurrently.
+#[test]
+fn racy_retain_replace() {
+    run_model(|| {
+        let idr = Arc::new(Idr::default());
+        let key = idr.insert(1).unwrap();
+
+        let idr1 = idr.clone();
+        let t1 = thread::spawn(move || idr1.retain(|_, value| *value != 1));
+
+        let idr2 = idr.clone();
+        let t2 = thread::spawn(move || show!(idr2.replace(key, 2)).map(|entry| *entry));
+
+        t1.join().unwrap();
+        let replaced = t2.join().unwrap();
+
+        // The replaced value is never removed, because it hasn't been checked.
+        let actual = idr.get(key, &EbrGuard::new()).map(|entry| *entry);
+        match replaced {
+            Some(1) => assert_eq!(actual, Some(2)),
+            None => assert_eq!(actual, None),
+            _ => unreachable!(),
+        }
+    });
+}
+
 // One thread removes existing entries, and another thread reuses the slots.
 #[test]
 fn remove_reuse() {
@@ -432,6 +549,99 @@ fn remove_reuse() {
     });
 }
 
+// One thread reserves a chain of slots, and another one reuses them concurrently.
+#[test]
+fn insert_many_reuse() {
+    run_model(|| {
+        let idr = Arc::new(Idr::<_, TinierConfig>::new());
+        idr.reserve(2);
+
+        let idr1 = idr.clone();
+        let t1 = thread::spawn(move || show!(idr1.insert_many([10, 11])));
+
+        // Take both slots and return the first one, so the head is the same.
+        let key1 = show!(idr.insert(1));
+        let key2 = show!(idr.insert(2));
+        if let Some(key1) = key1 {
+            assert!(show!(idr.remove(key1)));
+        }
+
+        let keys = t1.join().unwrap();
+
+        let guard = EbrGuard::new();
+        for (key, value) in keys.iter().zip([10, 11]) {
+            assert_eq!(idr.get(*key, &guard).unwrap(), value);
+        }
+        if let Some(key2) = key2 {
+            assert!(!keys.contains(&key2));
+            assert_eq!(idr.get(key2, &guard).unwrap(), 2);
+        }
+        assert_eq!(idr.len(), keys.len() + usize::from(key2.is_some()));
+    });
+}
+
+// One thread retires a slot of the full page, and another one collects stats.
+#[test]
+fn retire_stats() {
+    run_model(|| {
+        // 31 bits for slot ids, 1 bit for generations.
+        let idr = Arc::new(Idr::<_, RetiringConfig>::new());
+
+        let key1 = idr.insert(1).unwrap();
+        idr.insert(2).unwrap();
+        assert!(idr.remove(key1));
+        let key1 = idr.insert(3).unwrap(); // the last generation
+
+        let idr1 = idr.clone();
+        let t1 = thread::spawn(move || assert!(show!(idr1.remove(key1))));
+
+        let page = show!(idr.stats()).pages[0];
+        assert_eq!(page.capacity, 2);
+        // The slot being retired can be counted twice or not at all.
+        assert!(page.free <= 1, "{page:?}");
+
+        t1.join().unwrap();
+
+        let page = idr.stats().pages[0];
+        assert_eq!((page.occupied, page.retired, page.free), (1, 1, 0));
+    });
+}
+
+// Threads reuse slots freed by each other with the FIFO policy.
+// Every thread has at most one entry, so insertions never fail.
+#[test]
+fn remove_reuse_fifo() {
+    run_model(|| {
+        let idr = Arc::new(Idr::<_, FifoConfig>::new());
+
+        let threads = (0..2)
+            .map(|i| {
+                let idr = idr.clone();
+                thread::spawn(move || {
+                    for value in [i, i + 2] {
+                        // Insertion can fail while another thread moves slots.
+                        let Some(key) = show!(idr.insert(value)) else {
+                            continue;
+                        };
+                        assert_eq!(idr.get(key, &EbrGuard::new()).unwrap(), value);
+                        assert!(show!(idr.remove(key)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // Freed slots aren't lost.
+        assert!(idr.is_empty());
+        let key1 = idr.insert(1).unwrap();
+        let key2 = idr.insert(2).unwrap();
+        assert_ne!(key1, key2);
+    });
+}
+
 // One thread inserts an entry, and another thread removes it.
 #[test]
 fn insert_share_remove() {
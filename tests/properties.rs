@@ -4,6 +4,7 @@
 //! * The IDR doesn't produce overlapping keys.
 //! * The IDR doesn't leave "lost" keys.
 //! * `get()`, `get_owned`, and `contains()` are consistent.
+//! * `len()` and `is_empty()` are consistent with active entries.
 //! * `RESERVED_BITS` are actually not used.
 //!
 //! The test is supposed to be deterministic.
@@ -18,7 +19,7 @@ use std::ops::Range;
 use indexmap::IndexMap;
 use proptest::prelude::*;
 
-use idr_ebr::{Config, DefaultConfig, EbrGuard, Idr, Key};
+use idr_ebr::{Config, DefaultConfig, EbrGuard, Idr, Key, ReusePolicy};
 
 const ACTIONS: Range<usize> = 1..1000;
 
@@ -177,6 +178,9 @@ fn run<C: Config>(actions: Vec<Action>) -> Result<(), TestCaseError> {
         apply_action::<C>(&idr, &mut active, action)?;
     }
 
+    prop_assert_eq!(idr.len(), active.map.len());
+    prop_assert_eq!(idr.is_empty(), active.map.is_empty());
+
     // Ensure the IDR contains all remaining entries.
     let mut expected_values = Vec::new();
     for (key, value) in active.drain() {
@@ -213,6 +217,16 @@ proptest! {
     fn tiny_config(actions in prop::collection::vec(action_strategy(), ACTIONS)) {
         run::<TinyConfig>(actions)?;
     }
+
+    #[test]
+    fn fifo_config(actions in prop::collection::vec(action_strategy(), ACTIONS)) {
+        run::<FifoConfig>(actions)?;
+    }
+
+    #[test]
+    fn quarantine_config(actions in prop::collection::vec(action_strategy(), ACTIONS)) {
+        run::<QuarantineConfig>(actions)?;
+    }
 }
 
 struct MediumConfig;
@@ -226,3 +240,17 @@ impl Config for TinyConfig {
     const INITIAL_PAGE_SIZE: u32 = 4;
     const RESERVED_BITS: u32 = 3;
 }
+
+struct FifoConfig;
+impl Config for FifoConfig {
+    const INITIAL_PAGE_SIZE: u32 = 4;
+    const RESERVED_BITS: u32 = 3;
+    const REUSE_POLICY: ReusePolicy = ReusePolicy::Fifo;
+}
+
+struct QuarantineConfig;
+impl Config for QuarantineConfig {
+    const INITIAL_PAGE_SIZE: u32 = 4;
+    const RESERVED_BITS: u32 = 3;
+    const REUSE_POLICY: ReusePolicy = ReusePolicy::Quarantine;
+}
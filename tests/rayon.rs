@@ -0,0 +1,79 @@
+#![cfg(feature = "rayon")]
+
+use std::collections::HashSet;
+
+use rayon::prelude::*;
+
+use idr_ebr::{Config, Idr};
+
+#[test]
+fn par_iter() {
+    let idr = Idr::default();
+    assert_eq!(idr.par_iter().count(), 0);
+
+    let keys = idr.insert_many(0..100_000u64);
+    for key in keys.iter().step_by(3) {
+        assert!(idr.remove(*key));
+    }
+
+    let visited = idr
+        .par_iter()
+        .map(|(key, entry)| (key, *entry))
+        .collect::<Vec<_>>();
+    assert_eq!(visited.len(), idr.len());
+
+    let expected = keys
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i % 3 != 0)
+        .map(|(i, key)| (*key, i as u64))
+        .collect::<HashSet<_>>();
+    assert_eq!(visited.into_iter().collect::<HashSet<_>>(), expected);
+
+    // Short-circuiting.
+    assert!((&idr).into_par_iter().any(|(_, entry)| *entry == 50_000));
+    assert!(!idr.par_iter().any(|(_, entry)| *entry == 3));
+}
+
+#[test]
+fn par_iter_concurrent() {
+    struct TinyConfig;
+    impl Config for TinyConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const RESERVED_BITS: u32 = 32;
+    }
+
+    let idr = Idr::<u64, TinyConfig>::new();
+    let keys = idr.insert_many(0..10_000);
+
+    rayon::join(
+        || {
+            for key in &keys[5_000..] {
+                assert!(idr.remove(*key));
+            }
+        },
+        || {
+            let count = idr.par_iter().filter(|(_, entry)| **entry < 5_000).count();
+            assert_eq!(count, 5_000);
+        },
+    );
+
+    assert_eq!(idr.par_iter().count(), 5_000);
+}
+
+#[test]
+fn typed_keys() {
+    use idr_ebr::{DefaultConfig, Global, TypedKey};
+
+    let idr = Idr::<u64, DefaultConfig, Global, TypedKey<u64>>::new();
+    let keys = idr.insert_many(0..1000);
+
+    let mut visited = idr
+        .par_iter()
+        .map(|(key, entry)| (key, *entry))
+        .collect::<Vec<_>>();
+    visited.sort_unstable();
+
+    let expected = keys.into_iter().zip(0..).collect::<Vec<_>>();
+    assert_eq!(visited, expected);
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+use idr_ebr::{ChangeEvent, Config, EbrGuard, Idr, Key};
+
+struct FeedConfig;
+impl Config for FeedConfig {
+    const CHANGE_FEED_CAPACITY: usize = 1 << 16;
+}
+
+fn sync_follower<C: Config>(
+    primary: &Idr<u64, FeedConfig>,
+    follower: &mut Idr<u64, C>,
+    changes: &mut impl Iterator<Item = Result<ChangeEvent, idr_ebr::Lagged>>,
+) -> usize {
+    let mut applied = 0;
+
+    for event in changes {
+        let event = event.expect("lagged");
+        let guard = EbrGuard::new();
+        let value = primary.get(event.key(), &guard).map(|entry| *entry);
+        follower.apply(event, value).unwrap();
+        applied += 1;
+    }
+
+    applied
+}
+
+fn entries<C: Config>(idr: &Idr<u64, C>) -> BTreeMap<Key, u64> {
+    let guard = EbrGuard::new();
+    idr.iter(&guard).map(|(key, entry)| (key, *entry)).collect()
+}
+
+#[test]
+fn events() {
+    let idr = Idr::<u64, FeedConfig>::new();
+    let mut changes = idr.changes();
+    assert!(changes.next().is_none());
+
+    let a = idr.insert(1).unwrap();
+    let b = idr.vacant_entry().unwrap();
+    let b_key = b.key();
+    b.insert(2);
+    drop(idr.replace(a, 3).unwrap());
+    drop(idr.update(b_key, |v| v + 1).unwrap());
+    assert!(idr.remove(a));
+    assert!(!idr.remove(a));
+    let c = idr.insert_many([4, 5]);
+    idr.retain(|key, _| key != c[0]);
+    drop(idr.take(b_key).unwrap());
+
+    let events = changes.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(
+        events,
+        [
+            ChangeEvent::Inserted(a),
+            ChangeEvent::Inserted(b_key),
+            ChangeEvent::Replaced(a),
+            ChangeEvent::Replaced(b_key),
+            ChangeEvent::Removed(a),
+            ChangeEvent::Inserted(c[0]),
+            ChangeEvent::Inserted(c[1]),
+            ChangeEvent::Removed(c[0]),
+            ChangeEvent::Removed(b_key),
+        ]
+    );
+
+    // The iterator isn't fused.
+    assert!(changes.next().is_none());
+    idr.insert(6).unwrap();
+    assert!(matches!(changes.next(), Some(Ok(ChangeEvent::Inserted(_)))));
+}
+
+#[test]
+fn disabled() {
+    let idr = Idr::<u64>::default();
+    let mut changes = idr.changes();
+    idr.insert(1).unwrap();
+    assert!(changes.next().is_none());
+}
+
+#[test]
+fn lagged() {
+    struct SmallFeedConfig;
+    impl Config for SmallFeedConfig {
+        const CHANGE_FEED_CAPACITY: usize = 4;
+    }
+
+    let idr = Idr::<u64, SmallFeedConfig>::new();
+    let mut changes = idr.changes();
+    let keys = idr.insert_many(0..10);
+
+    let lagged = changes.next().unwrap().unwrap_err();
+    assert_eq!(lagged.skipped(), 6);
+
+    let events = changes.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+    let expected = keys[6..].iter().map(|key| ChangeEvent::Inserted(*key));
+    assert!(events.into_iter().eq(expected));
+}
+
+#[test]
+fn follower() {
+    let primary = Idr::<u64, FeedConfig>::new();
+    let mut changes = primary.changes();
+    let mut follower = Idr::<u64>::default();
+
+    let keys = primary.insert_many(0..100);
+    for key in keys.iter().step_by(2) {
+        assert!(primary.remove(*key));
+    }
+    for key in keys.iter().skip(1).step_by(4) {
+        drop(primary.update(*key, |v| v * 10).unwrap());
+    }
+    primary.insert_many(100..150);
+
+    sync_follower(&primary, &mut follower, &mut changes);
+    assert_eq!(entries(&follower), entries(&primary));
+
+    // Stale keys don't resolve on the follower too.
+    for key in keys.iter().step_by(2) {
+        assert!(!follower.contains(*key));
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn follower_concurrent() {
+    const THREADS: u64 = 4;
+    const ITERS: u64 = 2000;
+
+    let primary = Idr::<u64, FeedConfig>::new();
+    let mut changes = primary.changes();
+    let mut follower = Idr::<u64>::default();
+
+    let done = AtomicBool::new(false);
+
+    thread::scope(|s| {
+        let writers = (0..THREADS)
+            .map(|t| {
+                let primary = &primary;
+                s.spawn(move || {
+                    let mut keys = Vec::new();
+                    for i in 0..ITERS {
+                        keys.push(primary.insert(t * ITERS + i).unwrap());
+                        match i % 3 {
+                            0 => assert!(primary.remove(keys.swap_remove(0))),
+                            1 => drop(primary.replace(keys[keys.len() / 2], i)),
+                            _ => {}
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        s.spawn(|| {
+            for writer in writers {
+                writer.join().unwrap();
+            }
+            done.store(true, Ordering::Release);
+        });
+
+        while !done.load(Ordering::Acquire) {
+            sync_follower(&primary, &mut follower, &mut changes);
+        }
+    });
+
+    sync_follower(&primary, &mut follower, &mut changes);
+    assert_eq!(entries(&follower), entries(&primary));
+}
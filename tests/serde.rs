@@ -0,0 +1,227 @@
+#![cfg(feature = "serde")]
+
+use idr_ebr::{Config, DefaultConfig, EbrGuard, Idr, Key};
+
+#[test]
+fn key() {
+    let idr = Idr::default();
+    let key = idr.insert(42).unwrap();
+
+    let json = serde_json::to_string(&key).unwrap();
+    assert_eq!(json, u64::from(key).to_string());
+    assert_eq!(serde_json::from_str::<Key>(&json).unwrap(), key);
+
+    assert!(serde_json::from_str::<Key>("0").is_err());
+}
+
+#[test]
+fn roundtrip() {
+    let idr = Idr::<String>::default();
+    let keys = (0..100)
+        .map(|i| idr.insert(i.to_string()).unwrap())
+        .collect::<Vec<_>>();
+
+    // Bump generations of some slots.
+    for key in keys.iter().step_by(3) {
+        assert!(idr.remove(*key));
+    }
+    let reinserted = idr.insert(String::from("new")).unwrap();
+
+    let json = serde_json::to_string(&idr).unwrap();
+    let restored = serde_json::from_str::<Idr<String>>(&json).unwrap();
+    assert_eq!(restored.len(), idr.len());
+
+    let guard = EbrGuard::new();
+    for (key, entry) in idr.iter(&guard) {
+        assert_eq!(restored.get(key, &guard).unwrap(), *entry);
+    }
+    assert_eq!(*restored.get(reinserted, &guard).unwrap(), "new");
+
+    // Stale keys don't resolve into new entries.
+    let new_keys = (0..100)
+        .map(|i| restored.insert(i.to_string()).unwrap())
+        .collect::<Vec<_>>();
+    for key in keys.iter().step_by(3) {
+        assert!(!new_keys.contains(key));
+        assert!(!restored.contains(*key));
+    }
+
+    // Vacant slots are linked into free lists.
+    assert_eq!(restored.len(), idr.len() + 100);
+}
+
+#[test]
+fn strict_reserved_bits() {
+    struct StrictConfig;
+    impl Config for StrictConfig {
+        const RESERVED_BITS: u32 = 8;
+        const STRICT_RESERVED_BITS: bool = true;
+    }
+
+    let idr = Idr::<u64, StrictConfig>::new();
+    let key = idr.insert_with_reserved(42, 0xab).unwrap();
+
+    let json = serde_json::to_string(&idr).unwrap();
+    let restored = serde_json::from_str::<Idr<u64, StrictConfig>>(&json).unwrap();
+
+    let guard = EbrGuard::new();
+    assert_eq!(restored.get(key, &guard).unwrap(), 42);
+    assert!(restored
+        .get(key.with_reserved::<StrictConfig>(0), &guard)
+        .is_none());
+}
+
+#[test]
+fn incompatible_config() {
+    struct SmallConfig;
+    impl Config for SmallConfig {
+        const MAX_PAGES: u32 = 20;
+        const RESERVED_BITS: u32 = 7;
+    }
+
+    let idr = Idr::<u64>::default();
+    idr.insert(42).unwrap();
+
+    let json = serde_json::to_string(&idr).unwrap();
+    let error = serde_json::from_str::<Idr<u64, SmallConfig>>(&json).unwrap_err();
+    assert!(error.to_string().contains("incompatible config"), "{error}");
+
+    // The same layout of keys is compatible.
+    let restored = serde_json::from_str::<Idr<u64, DefaultConfig>>(&json).unwrap();
+    assert_eq!(restored.len(), 1);
+}
+
+#[test]
+fn invalid_keys() {
+    let json = r#"{"slot_bits":32,"generation_bits":32,"entries":[[1,42]],"vacant":[]}"#;
+    let error = serde_json::from_str::<Idr<u64>>(json).unwrap_err();
+    assert!(error.to_string().contains("invalid key 1"), "{error}");
+
+    let json = r#"{"slot_bits":32,"generation_bits":32,"entries":[[32,1],[32,2]],"vacant":[]}"#;
+    let error = serde_json::from_str::<Idr<u64>>(json).unwrap_err();
+    assert!(error.to_string().contains("duplicate key 32"), "{error}");
+}
+
+#[test]
+fn typed_keys() {
+    use idr_ebr::{Global, TypedKey};
+
+    type Typed = Idr<String, DefaultConfig, Global, TypedKey<String>>;
+
+    let idr = Typed::new();
+    let key = idr.insert(String::from("foo")).unwrap();
+    assert_eq!(
+        serde_json::to_string(&key).unwrap(),
+        serde_json::to_string(&Key::from(key)).unwrap()
+    );
+
+    // Snapshots don't depend on the key type.
+    let json = serde_json::to_string(&idr).unwrap();
+    let untyped = serde_json::from_str::<Idr<String>>(&json).unwrap();
+    assert_eq!(*untyped.get(key.into(), &EbrGuard::new()).unwrap(), "foo");
+
+    let restored = serde_json::from_str::<Typed>(&json).unwrap();
+    let key = serde_json::from_str(&serde_json::to_string(&key).unwrap()).unwrap();
+    assert_eq!(*restored.get(key, &EbrGuard::new()).unwrap(), "foo");
+}
+
+#[test]
+fn instance_tag() {
+    struct TaggedConfig;
+    impl Config for TaggedConfig {
+        const RESERVED_BITS: u32 = 8;
+        const INSTANCE_TAG: bool = true;
+    }
+
+    let idr = Idr::<u64, TaggedConfig>::new();
+    let keys = idr.insert_many(0..10);
+    assert!(idr.remove(keys[0]));
+
+    // The tag of the snapshot is restored, so keys stay valid.
+    let json = serde_json::to_string(&idr).unwrap();
+    let restored = serde_json::from_str::<Idr<u64, TaggedConfig>>(&json).unwrap();
+    assert_eq!(restored.instance_tag(), idr.instance_tag());
+    assert!(keys[1..].iter().all(|key| restored.contains(*key)));
+    assert!(!restored.contains(keys[0]));
+
+    let new_key = restored.insert(10).unwrap();
+    assert_eq!(
+        new_key.reserved::<TaggedConfig>(),
+        idr.instance_tag().unwrap()
+    );
+
+    // Entries with different tags are rejected.
+    let tag = idr.instance_tag().unwrap();
+    let foreign = u64::from(keys[0].with_reserved::<TaggedConfig>(tag % 0xff + 1));
+    let json = json.replacen("\"entries\":[", &format!("\"entries\":[[{foreign},0],"), 1);
+    let error = serde_json::from_str::<Idr<u64, TaggedConfig>>(&json).unwrap_err();
+    assert!(
+        error.to_string().contains("another instance tag"),
+        "{error}"
+    );
+}
+
+#[test]
+fn retired_slots() {
+    struct RetiringConfig;
+    impl Config for RetiringConfig {
+        const RESERVED_BITS: u32 = 30;
+        const RETIRE_SLOTS_ON_WRAP: bool = true;
+    }
+
+    // 2 bits for generations, so the slot is retired after 4 removals.
+    let idr = Idr::<u64, RetiringConfig>::new();
+    let kept = idr.insert(0).unwrap();
+    let mut stale = Vec::new();
+    for i in 0..4 {
+        let key = idr.insert(i).unwrap();
+        assert!(idr.remove(key));
+        stale.push(key);
+    }
+    assert_eq!(idr.retired(), 1);
+
+    let json = serde_json::to_string(&idr).unwrap();
+    let restored = serde_json::from_str::<Idr<u64, RetiringConfig>>(&json).unwrap();
+    assert_eq!(restored.retired(), 1);
+    assert_eq!(restored.stats(), idr.stats());
+    assert!(restored.contains(kept));
+
+    // The retired slot isn't reused.
+    let new_keys = restored.insert_many(0..10);
+    assert!(stale.iter().all(|key| !new_keys.contains(key)));
+    assert!(stale.iter().all(|key| !restored.contains(*key)));
+
+    // Snapshots without retired slots are accepted.
+    let json = serde_json::to_string(&Idr::<u64, RetiringConfig>::new()).unwrap();
+    let json = json.replace(",\"retired\":[]", "");
+    assert!(!json.contains("retired"));
+    let restored = serde_json::from_str::<Idr<u64, RetiringConfig>>(&json).unwrap();
+    assert_eq!(restored.retired(), 0);
+}
+
+#[test]
+fn concurrent_remove() {
+    let idr = Idr::<u64>::default();
+    let keys = (0..10_000)
+        .map(|i| idr.insert(i).unwrap())
+        .collect::<Vec<_>>();
+
+    std::thread::scope(|s| {
+        s.spawn(|| {
+            for key in &keys {
+                assert!(idr.remove(*key));
+            }
+        });
+
+        // Every slot is recorded once, even if it's removed concurrently.
+        for _ in 0..20 {
+            let json = serde_json::to_string(&idr).unwrap();
+            let restored = serde_json::from_str::<Idr<u64>>(&json).unwrap();
+
+            let guard = EbrGuard::new();
+            for (key, value) in restored.iter(&guard) {
+                assert_eq!(keys[*value as usize], key);
+            }
+        }
+    });
+}
@@ -1,4 +1,4 @@
-use idr_ebr::{Config, EbrGuard, Idr, Key};
+use idr_ebr::{Config, DefaultConfig, EbrGuard, Global, Idr, IdrKey, Key, Stats, TypedKey};
 
 #[test]
 fn smoke() {
@@ -141,3 +141,541 @@ fn to_owned() {
     assert!(idr.remove(key));
     assert!(entry.to_owned().is_none());
 }
+
+#[test]
+fn take() {
+    let idr = Idr::<i32>::default();
+    let key = idr.insert(42).unwrap();
+
+    let guard = EbrGuard::new();
+    let borrowed = idr.get(key, &guard).unwrap();
+
+    let owned = idr.take(key).unwrap();
+    assert_eq!(owned, 42);
+    assert!(!idr.contains(key));
+    assert!(idr.take(key).is_none());
+    assert!(!idr.remove(key));
+
+    // Existing handles are still valid.
+    assert_eq!(borrowed, 42);
+
+    // The slot is reused.
+    let key2 = idr.insert(43).unwrap();
+    assert_ne!(key2, key);
+    assert_eq!(idr.take(key2).unwrap(), 43);
+    assert_eq!(owned, 42);
+}
+
+#[test]
+fn replace() {
+    let idr = Idr::<i32>::default();
+    let key = idr.insert(1).unwrap();
+
+    let guard = EbrGuard::new();
+    let first = idr.get(key, &guard).unwrap();
+
+    // `replace()`
+    assert_eq!(idr.replace(key, 2).unwrap(), 1);
+    assert_eq!(idr.get(key, &guard).unwrap(), 2);
+    assert_eq!(first, 1);
+
+    // `compare_and_swap()`
+    assert!(idr.compare_and_swap(key, &first, 3).is_none());
+    let second = idr.get(key, &guard).unwrap();
+    assert_eq!(idr.compare_and_swap(key, &second, 3).unwrap(), 2);
+    assert_eq!(idr.get(key, &guard).unwrap(), 3);
+
+    // `update()`
+    assert_eq!(idr.update(key, |old| old + 1).unwrap(), 3);
+    assert_eq!(idr.get(key, &guard).unwrap(), 4);
+
+    // Entries of other keys cannot be used.
+    let other_key = idr.insert(5).unwrap();
+    let other = idr.get(other_key, &guard).unwrap();
+    assert!(idr.compare_and_swap(key, &other, 6).is_none());
+    assert_eq!(idr.get(key, &guard).unwrap(), 4);
+
+    // Removed entries cannot be replaced.
+    assert!(idr.remove(key));
+    assert!(idr.replace(key, 7).is_none());
+    assert!(idr.update(key, |old| old + 1).is_none());
+    let third = idr.get(other_key, &guard).unwrap();
+    assert!(idr.compare_and_swap(key, &third, 8).is_none());
+    assert!(!idr.contains(key));
+}
+
+#[test]
+fn len() {
+    let idr = Idr::<i32>::default();
+    assert_eq!(idr.len(), 0);
+    assert!(idr.is_empty());
+
+    let keys = (0..100).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    assert_eq!(idr.len(), 100);
+    assert!(!idr.is_empty());
+
+    // Reserved entries are counted.
+    let entry = idr.vacant_entry().unwrap();
+    assert_eq!(idr.len(), 101);
+    drop(entry);
+    assert_eq!(idr.len(), 100);
+
+    assert!(idr.remove(keys[0]));
+    assert!(idr.take(keys[1]).is_some());
+    assert!(idr.replace(keys[2], 42).is_some());
+    assert_eq!(idr.len(), 98);
+
+    for key in &keys[2..] {
+        assert!(idr.remove(*key));
+    }
+    assert_eq!(idr.len(), 0);
+    assert!(idr.is_empty());
+}
+
+#[test]
+fn stats() {
+    struct TinyConfig;
+    impl Config for TinyConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 5;
+        const RESERVED_BITS: u32 = 32;
+    }
+
+    let idr = Idr::<u64, TinyConfig>::new();
+    assert_eq!(idr.stats(), Stats::default());
+
+    let keys = (0..6).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    assert!(idr.remove(keys[0]));
+
+    let stats = idr.stats();
+    assert_eq!(stats.allocated_pages, 2);
+    assert_eq!(stats.capacity, 4 + 8);
+    assert_eq!(stats.occupied, 5);
+    assert_eq!(stats.free, 7);
+    assert_eq!(stats.pages.len(), 2);
+    assert_eq!(stats.pages[0].page_no, 0);
+    assert_eq!(stats.pages[0].occupied + stats.pages[1].occupied, 5);
+    assert_eq!(stats.pages[1].page_no, 1);
+    assert_eq!(stats.pages[1].capacity, 8);
+    assert_eq!(stats.slots_bytes % stats.capacity, 0);
+    assert_eq!(
+        stats.slots_bytes,
+        stats.pages[0].slots_bytes + stats.pages[1].slots_bytes
+    );
+    assert!(stats.containers_bytes >= 5 * std::mem::size_of::<u64>());
+}
+
+#[test]
+fn reserve() {
+    struct TinyConfig;
+    impl Config for TinyConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 5;
+        const RESERVED_BITS: u32 = 32;
+    }
+
+    let idr = Idr::<u32, TinyConfig>::with_capacity(0);
+    assert_eq!(idr.stats().allocated_pages, 0);
+
+    let idr = Idr::<u32, TinyConfig>::with_capacity(5);
+    assert_eq!(idr.stats().allocated_pages, 2);
+    assert_eq!(idr.stats().capacity, 12);
+
+    // Allocated pages are taken into account.
+    idr.reserve(12);
+    assert_eq!(idr.stats().allocated_pages, 2);
+
+    for i in 0..10 {
+        idr.insert(i).unwrap();
+    }
+    assert_eq!(idr.stats().allocated_pages, 2);
+
+    // Only free slots are taken into account.
+    idr.reserve(2);
+    assert_eq!(idr.stats().allocated_pages, 2);
+    idr.reserve(3);
+    assert_eq!(idr.stats().allocated_pages, 3);
+
+    // All pages are allocated if the capacity is exceeded.
+    idr.reserve(usize::MAX);
+    assert_eq!(idr.stats().allocated_pages, 5);
+    assert_eq!(idr.stats().free, 124 - 10);
+}
+
+#[test]
+fn shrink_to_fit() {
+    struct TinyConfig;
+    impl Config for TinyConfig {
+        const INITIAL_PAGE_SIZE: u32 = 4;
+        const MAX_PAGES: u32 = 5;
+        const RESERVED_BITS: u32 = 32;
+    }
+
+    let mut idr = Idr::<u32, TinyConfig>::new();
+    idr.shrink_to_fit();
+    assert_eq!(idr.stats().allocated_pages, 0);
+
+    let keys = (0..28).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    assert_eq!(idr.stats().allocated_pages, 3);
+
+    // Only trailing empty pages are deallocated.
+    let (first, rest) = keys.split_first().unwrap();
+    for key in rest {
+        assert!(idr.remove(*key));
+    }
+    idr.shrink_to_fit();
+    assert_eq!(idr.stats().allocated_pages, 1);
+    assert_eq!(idr.get(*first, &EbrGuard::new()).unwrap(), 0);
+
+    assert!(idr.remove(*first));
+    idr.shrink_to_fit();
+    assert_eq!(idr.stats(), Stats::default());
+
+    // Pages are allocated again, but old keys are still invalid.
+    let new_keys = (0..28).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    assert_eq!(idr.stats().allocated_pages, 3);
+
+    for key in &keys {
+        assert!(!idr.contains(*key));
+        assert!(!new_keys.contains(key));
+    }
+    for (i, key) in new_keys.iter().enumerate() {
+        assert_eq!(idr.get(*key, &EbrGuard::new()).unwrap(), i as u32);
+    }
+}
+
+#[test]
+fn get_mut() {
+    let mut idr = Idr::default();
+    let key = idr.insert(String::from("foo")).unwrap();
+
+    idr.get_mut(key).unwrap().push_str("bar");
+    assert_eq!(*idr.get(key, &EbrGuard::new()).unwrap(), "foobar");
+
+    let owned = idr.get_owned(key).unwrap();
+    assert!(idr.get_mut(key).is_none());
+    drop(owned);
+    assert!(idr.get_mut(key).is_some());
+
+    assert!(idr.remove(key));
+    assert!(idr.get_mut(key).is_none());
+
+    let key2 = idr.insert(String::from("baz")).unwrap();
+    assert!(idr.get_mut(key).is_none());
+    assert_eq!(*idr.get_mut(key2).unwrap(), "baz");
+}
+
+#[test]
+fn iter_mut() {
+    let mut idr = Idr::default();
+    let keys = (0..100).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    assert!(idr.remove(keys[10]));
+
+    // Shared entries are skipped.
+    let owned = idr.get_owned(keys[20]).unwrap();
+
+    for (_, value) in &mut idr {
+        *value += 1000;
+    }
+
+    let guard = EbrGuard::new();
+    for (i, key) in keys.iter().enumerate() {
+        let expected = match i {
+            10 => continue,
+            20 => 20,
+            _ => i + 1000,
+        };
+        assert_eq!(idr.get(*key, &guard).unwrap(), expected);
+    }
+    assert_eq!(owned, 20);
+
+    let visited = idr.iter_mut().map(|(key, _)| key).collect::<Vec<_>>();
+    assert_eq!(visited.len(), 98);
+    assert!(!visited.contains(&keys[10]));
+    assert!(!visited.contains(&keys[20]));
+}
+
+#[test]
+fn drain() {
+    let mut idr = Idr::default();
+    let keys = (0..100).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    let owned = idr.get_owned(keys[5]).unwrap();
+    let pages = idr.stats().allocated_pages;
+
+    let drained = idr.drain().collect::<Vec<_>>();
+    assert_eq!(drained.len(), 100);
+    for ((key, entry), (i, expected_key)) in drained.iter().zip(keys.iter().enumerate()) {
+        assert_eq!(key, expected_key);
+        assert_eq!(*entry, i);
+    }
+
+    assert!(idr.is_empty());
+    assert_eq!(idr.stats().allocated_pages, pages);
+    assert!(!idr.contains(keys[5]));
+    assert_eq!(owned, 5);
+
+    // Remaining entries are removed on drop.
+    let keys = (0..10).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    let mut drain = idr.drain();
+    assert!(keys.contains(&drain.next().unwrap().0));
+    drop(drain);
+    assert!(idr.is_empty());
+}
+
+#[test]
+fn into_iter() {
+    let idr = Idr::default();
+    let keys = (0..100).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+    assert!(idr.remove(keys[50]));
+
+    let entries = idr.into_iter().collect::<Vec<_>>();
+    assert_eq!(entries.len(), 99);
+    assert!(entries.iter().all(|(key, entry)| keys[**entry] == *key));
+}
+
+#[test]
+fn retain() {
+    let idr = Idr::default();
+    let keys = (0..100).map(|i| idr.insert(i).unwrap()).collect::<Vec<_>>();
+
+    let guard = EbrGuard::new();
+    let entry = idr.get(keys[1], &guard).unwrap();
+
+    let mut visited = Vec::new();
+    idr.retain(|key, value| {
+        visited.push(key);
+        value % 2 == 0
+    });
+
+    assert_eq!(visited, keys);
+    assert_eq!(idr.len(), 50);
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(idr.contains(*key), i % 2 == 0);
+    }
+
+    // Removed entries are still accessible using existing handles.
+    assert_eq!(entry, 1);
+
+    // Slots are reused.
+    let stats = idr.stats();
+    for i in 0..50 {
+        idr.insert(i).unwrap();
+    }
+    assert_eq!(idr.stats().capacity, stats.capacity);
+
+    idr.clear();
+    assert!(idr.is_empty());
+    assert_eq!(idr.iter(&guard).count(), 0);
+}
+
+#[test]
+fn insert_many() {
+    let idr = Idr::default();
+
+    // The size hint is exact.
+    let keys = idr.insert_many(0..100);
+    assert_eq!(keys.len(), 100);
+
+    // The size hint is underestimated.
+    let keys2 = idr.insert_many((100..200).filter(|_| true));
+    assert_eq!(keys2.len(), 100);
+
+    // The size hint is overestimated.
+    let keys3 = idr.insert_many(
+        (200..300)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .take_while(|i| *i < 250),
+    );
+    assert_eq!(keys3.len(), 50);
+
+    assert!(idr.insert_many(std::iter::empty()).is_empty());
+    assert_eq!(idr.len(), 250);
+
+    let guard = EbrGuard::new();
+    for (i, key) in keys.iter().chain(&keys2).chain(&keys3).enumerate() {
+        assert_eq!(idr.get(*key, &guard).unwrap(), i);
+    }
+
+    // Unused reserved slots are freed.
+    let stats = idr.stats();
+    assert_eq!(stats.occupied, 250);
+    assert_eq!(stats.free, stats.capacity - 250);
+}
+
+#[test]
+fn remove_many() {
+    let idr = Idr::default();
+    let keys = idr.insert_many(0..100);
+
+    assert_eq!(idr.remove_many(&keys[..50]), 50);
+    assert_eq!(idr.remove_many(&keys), 50);
+    assert_eq!(idr.remove_many(&keys), 0);
+    assert!(idr.is_empty());
+    assert_eq!(idr.remove_many(&[]), 0);
+}
+
+#[test]
+fn get_many() {
+    let idr = Idr::default();
+    let keys = idr.insert_many(0..100);
+    assert!(idr.remove(keys[10]));
+
+    let guard = EbrGuard::new();
+    let entries = idr.get_many(&keys, &guard);
+    assert_eq!(entries.len(), 100);
+
+    for (i, entry) in entries.into_iter().enumerate() {
+        if i == 10 {
+            assert!(entry.is_none());
+        } else {
+            assert_eq!(entry.unwrap(), i);
+        }
+    }
+}
+
+#[test]
+fn iter_rev() {
+    let idr = Idr::default();
+    let keys = idr.insert_many(0..100);
+    assert!(idr.remove(keys[0]));
+    assert!(idr.remove(keys[99]));
+
+    let guard = EbrGuard::new();
+    let forward = idr.iter(&guard).map(|(key, _)| key).collect::<Vec<_>>();
+    let mut backward = idr
+        .iter(&guard)
+        .rev()
+        .map(|(key, _)| key)
+        .collect::<Vec<_>>();
+    backward.reverse();
+    assert_eq!(forward, &keys[1..99]);
+    assert_eq!(backward, forward);
+
+    // Both ends meet in the middle.
+    let mut iter = idr.iter(&guard);
+    let mut visited = Vec::new();
+    while let Some((key, _)) = iter.next() {
+        visited.push(key);
+        if let Some((key, _)) = iter.next_back() {
+            visited.push(key);
+        }
+    }
+    visited.sort();
+    assert_eq!(visited, forward);
+    assert!(iter.next().is_none());
+    assert!(iter.next_back().is_none());
+}
+
+#[test]
+fn iter_from() {
+    let idr = Idr::default();
+    let keys = idr.insert_many(0..100);
+
+    let guard = EbrGuard::new();
+    for (i, key) in keys.iter().enumerate() {
+        let tail = idr
+            .iter_from(*key, &guard)
+            .map(|(key, _)| key)
+            .collect::<Vec<_>>();
+        assert_eq!(tail, &keys[i..]);
+
+        let rev = idr.iter_from(*key, &guard).rev().count();
+        assert_eq!(rev, keys.len() - i);
+    }
+
+    // The key is still usable as a cursor after removal.
+    assert!(idr.remove(keys[50]));
+    let (key, entry) = idr.iter_from(keys[50], &guard).next().unwrap();
+    assert_eq!(key, keys[51]);
+    assert_eq!(entry, 51);
+
+    // Keys beyond allocated pages.
+    let key = Key::try_from(u64::from(u32::MAX >> 1)).unwrap();
+    assert!(idr.iter_from(key, &guard).next().is_none());
+    assert!(idr.get_next(key, &guard).is_none());
+}
+
+#[test]
+fn get_next() {
+    let idr = Idr::default();
+    let keys = idr.insert_many(0..100);
+    assert!(idr.remove(keys[50]));
+
+    let mut cursor = keys[0];
+    let mut visited = vec![cursor];
+    while let Some((key, entry)) = idr.get_next(cursor, &EbrGuard::new()) {
+        assert_eq!(idr.get(key, &EbrGuard::new()).unwrap(), *entry);
+        visited.push(key);
+        cursor = key;
+    }
+
+    let mut expected = keys.clone();
+    expected.remove(50);
+    assert_eq!(visited, expected);
+}
+
+#[test]
+fn iter_owned() {
+    fn assert_send<T: Send>(value: T) -> T {
+        value
+    }
+
+    let idr = Idr::default();
+    let keys = idr.insert_many(0..100);
+
+    let mut iter = assert_send(idr.iter_owned());
+    let mut visited = Vec::new();
+
+    for (key, entry) in iter.by_ref() {
+        assert_eq!(keys[*entry], key);
+        visited.push(key);
+
+        // Modifications during the iteration.
+        if *entry == 10 {
+            assert!(idr.remove(keys[11]));
+            assert!(idr.remove(keys[5]));
+            idr.insert(100).unwrap();
+        }
+    }
+
+    // The reused slot is visited only if it's after the current position.
+    let mut expected = keys.clone();
+    expected.remove(11);
+    assert_eq!(visited, expected);
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn typed_keys() {
+    struct User(&'static str);
+    type Users = Idr<User, DefaultConfig, Global, TypedKey<User>>;
+
+    let mut idr = Users::new();
+    let alice = idr.insert(User("alice")).unwrap();
+    let keys = idr.insert_many([User("bob"), User("carol")]);
+    let entry = idr.vacant_entry().unwrap();
+    let dave = entry.key();
+    entry.insert(User("dave"));
+
+    // The same layout as untyped keys.
+    let raw = Key::from(alice);
+    assert_eq!(TypedKey::<User>::from_key(raw), alice);
+    assert_eq!(u64::from(alice), u64::from(raw));
+
+    let guard = EbrGuard::new();
+    assert_eq!(idr.get(alice, &guard).unwrap().0, "alice");
+    assert_eq!(idr.get_many(&keys, &guard)[1].unwrap().0, "carol");
+    assert_eq!(idr.get_next(alice, &guard).unwrap().0, keys[0]);
+    let visited = idr.iter(&guard).map(|(key, _)| key).collect::<Vec<_>>();
+    assert_eq!(visited, [alice, keys[0], keys[1], dave]);
+    drop(guard);
+
+    idr.retain(|key, _| key != keys[0]);
+    assert!(!idr.contains(keys[0]));
+    assert_eq!(idr.take(dave).unwrap().0, "dave");
+    assert_eq!(idr.get_mut(alice).unwrap().0, "alice");
+
+    let drained = idr.drain().map(|(key, _)| key).collect::<Vec<_>>();
+    assert_eq!(drained, [alice, keys[1]]);
+}